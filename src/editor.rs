@@ -0,0 +1,271 @@
+//! Editing forms with the user's editor
+
+use {
+    crate::{MainError, cache::write_editable_file, config::Config},
+    clap::ArgMatches,
+    std::{
+        borrow::Cow,
+        fmt::Display,
+        fs,
+        io::stdin,
+        path::Path,
+        process::{Command, Stdio},
+    },
+    tempfile::tempdir,
+};
+
+/// Ask a yes/no question on stderr.
+pub fn confirm(question: &str) -> Result<bool, MainError> {
+    let mut line = String::with_capacity(3);
+    loop {
+        eprintln!("{question} (yes/no)?:");
+        line.clear();
+        stdin().read_line(&mut line).map_err(MainError::ReadLine)?;
+        match line.trim() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            response => eprintln!("unknown option `{response}`"),
+        }
+    }
+}
+
+/// Editors used when none is set, in order of preference.
+const FALLBACK_EDITORS: &[&str] = &["nano", "vi"];
+/// GUI editors that return before the file is closed unless given one of these flags.
+const GUI_EDITORS: &[(&[&str], &[&str])] = &[
+    (
+        &["code", "code-insiders", "codium", "cursor", "windsurf"],
+        &["--wait", "-w"],
+    ),
+    (&["subl", "sublime_text"], &["--wait", "-w"]),
+    (&["zed", "zeditor"], &["--wait", "-w"]),
+    (&["atom", "mate", "xed"], &["--wait", "-w"]),
+    (&["gedit"], &["--wait", "-s", "--standalone"]),
+    (&["gvim", "mvim"], &["--nofork", "-f"]),
+    (&["kate"], &["--block", "-b"]),
+];
+
+/// Split a command into words like a POSIX shell, without any expansions.
+fn split(command: &str) -> Result<Vec<String>, MainError> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            char if char.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(char) => word.push(char),
+                        None => return Err(MainError::ParseEditor(command.to_string())),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(char @ ('"' | '\\' | '$' | '`')) => word.push(char),
+                            Some(char) => {
+                                word.push('\\');
+                                word.push(char);
+                            }
+                            None => return Err(MainError::ParseEditor(command.to_string())),
+                        },
+                        Some(char) => word.push(char),
+                        None => return Err(MainError::ParseEditor(command.to_string())),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(char) => word.get_or_insert_default().push(char),
+                None => return Err(MainError::ParseEditor(command.to_string())),
+            },
+            char => word.get_or_insert_default().push(char),
+        }
+    }
+    words.extend(word);
+
+    Ok(words)
+}
+
+fn in_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| {
+            let path = dir.join(program);
+            path.is_file()
+                || path
+                    .with_extension(std::env::consts::EXE_EXTENSION)
+                    .is_file()
+        })
+    })
+}
+
+pub struct Editor {
+    command: String,
+    args: Vec<String>,
+}
+impl Editor {
+    /// Get the editor from the `editor` and `arg` flags, falling back to the config and then to
+    /// [FALLBACK_EDITORS].
+    pub fn from_args(args: &mut ArgMatches, config: &Config) -> Result<Self, MainError> {
+        let mut words = match args
+            .remove_one::<String>("editor")
+            .or_else(|| config.editor().map(String::from))
+        {
+            Some(editor) => split(&editor)?,
+            None => FALLBACK_EDITORS
+                .iter()
+                .find(|editor| in_path(editor))
+                .map(|editor| vec![editor.to_string()])
+                .ok_or(MainError::NoEditor)?,
+        };
+        if words.is_empty() {
+            return Err(MainError::NoEditor);
+        }
+        let command = words.remove(0);
+        words.extend(
+            args.remove_many::<String>("arg")
+                .map(|args| args.collect())
+                .unwrap_or_else(|| config.editor_args().to_vec()),
+        );
+
+        let mut editor = Self {
+            command,
+            args: words,
+        };
+        editor.add_wait_flag();
+        Ok(editor)
+    }
+
+    /// Make known GUI editors wait for the file to be closed.
+    fn add_wait_flag(&mut self) {
+        let name = Path::new(&self.command)
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.command);
+
+        if let Some((_, flags)) = GUI_EDITORS
+            .iter()
+            .find(|(editors, _)| editors.contains(&name))
+            && !self.args.iter().any(|arg| flags.contains(&arg.as_str()))
+        {
+            log::warn!(
+                "`{name}` returns before the file is closed, passing `{}`",
+                flags[0]
+            );
+            self.args.insert(0, flags[0].to_string());
+        }
+    }
+
+    /// Edit `contents` in a temporary file named `file_name` until it passes `validate`.
+    ///
+    /// If `draft` is set, it is edited instead of a temporary file so that it is kept if the user
+    /// gives up or the process dies, and it is removed once the document is accepted.
+    /// If `prompt` is set, the document will be shown and the user will be asked to confirm it.
+    /// Returns `None` if the user gave up.
+    pub fn edit<F, T, E>(
+        &self,
+        file_name: &str,
+        contents: &str,
+        draft: Option<&Path>,
+        prompt: Option<&str>,
+        validate: F,
+    ) -> Result<Option<T>, MainError>
+    where
+        F: Fn(&str) -> Result<T, E>,
+        T: Display,
+        E: Display,
+    {
+        let dir;
+        let path = match draft {
+            Some(draft) => draft.to_path_buf(),
+            None => {
+                dir = tempdir().map_err(MainError::CreateTempDir)?;
+                dir.path().join(file_name)
+            }
+        };
+
+        write_editable_file(Cow::Owned(path.clone()), contents.as_bytes())?;
+
+        let mut command = Command::new(&self.command);
+        command
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .args(&self.args)
+            .arg(&path);
+
+        loop {
+            command
+                .output()
+                .map_err(|error| MainError::ExecuteCommand(error, format!("{command:?}")))?;
+
+            let contents = fs::read_to_string(&path)
+                .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.clone())))?;
+
+            let document = match validate(&contents) {
+                Ok(document) => match prompt {
+                    Some(prompt) => {
+                        eprintln!("{document}");
+                        confirm(prompt)?.then_some(document)
+                    }
+                    None => Some(document),
+                },
+                Err(error) => {
+                    eprintln!("{error}");
+                    if prompt.is_some() && !confirm("Exit:")? {
+                        continue;
+                    }
+                    None
+                }
+            };
+
+            if draft.is_some() {
+                match document {
+                    Some(_) => fs::remove_file(&path)
+                        .map_err(|error| MainError::RemoveFile(error, Cow::Owned(path)))?,
+                    None => eprintln!("Your draft was saved to `{}`", path.display()),
+                }
+            }
+            return Ok(document);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words() {
+        assert_eq!(split("code --wait").unwrap(), ["code", "--wait"]);
+        assert_eq!(
+            split(r#"  "/Applications/Sublime Text.app/subl" -n 'a b'\ c "" "#).unwrap(),
+            ["/Applications/Sublime Text.app/subl", "-n", "a b c", ""]
+        );
+        assert_eq!(split(r#"vim "\"q\" \n""#).unwrap(), ["vim", r#""q" \n"#]);
+        assert!(split("vim 'unterminated").is_err());
+
+        let mut editor = Editor {
+            command: "/usr/bin/code".to_string(),
+            args: vec!["--new-window".to_string()],
+        };
+        editor.add_wait_flag();
+        assert_eq!(editor.args, ["--wait", "--new-window"]);
+        editor.add_wait_flag();
+        assert_eq!(editor.args, ["--wait", "--new-window"]);
+
+        let mut editor = Editor {
+            command: "emacsclient".to_string(),
+            args: vec!["-t".to_string()],
+        };
+        editor.add_wait_flag();
+        assert_eq!(editor.args, ["-t"]);
+    }
+}
@@ -0,0 +1,206 @@
+//! Serde helpers shared between the release form and the profile
+
+pub mod check;
+
+use {
+    serde::{
+        Deserialize, Deserializer, Serializer,
+        de::{self, DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+    },
+    std::{fmt, path::PathBuf},
+    toml_edit::{Date, DocumentMut, Item, TomlError, Value},
+};
+
+const ERROR: &str = "string cannot be empty";
+
+/// Set a top level value, keeping the comments around it if it already exists.
+pub fn set_value(document: &mut DocumentMut, key: &str, mut value: Value) {
+    match document.get_mut(key).and_then(Item::as_value_mut) {
+        Some(old) => {
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+        }
+        None => {
+            document.insert(key, Item::Value(value));
+        }
+    }
+}
+
+pub fn serialize_iso_8601<S>(date: &Date, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&date.to_string())
+}
+#[expect(clippy::ref_option)]
+pub fn serialize_some_iso_8601<S>(date: &Option<Date>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match date {
+        Some(date) => serialize_iso_8601(date, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserialize a string and run `check` on it.
+fn deserialize_checked<'de, D, F>(deserializer: D, check: F) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&str) -> Result<(), String>,
+{
+    deserialize_non_empty_string(deserializer)
+        .and_then(|string| check(&string).map(|_| string).map_err(de::Error::custom))
+}
+
+pub fn deserialize_url<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_checked(deserializer, check::url)
+}
+/// Deserialize an array of strings, converting each of them with `check`.
+///
+/// Every invalid entry is reported instead of only the first one.
+fn deserialize_vec_checked<'de, D, F, T>(
+    deserializer: D,
+    key: &str,
+    check: F,
+) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    F: Fn(String) -> Result<T, String>,
+{
+    let (values, errors) = Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, string)| {
+            if string.is_empty() {
+                Err(ERROR.to_string())
+            } else {
+                check(string)
+            }
+            .map_err(|error| format!("{key}[{i}]: {error}"))
+        })
+        .partition::<Vec<_>, _>(Result::is_ok);
+
+    if errors.is_empty() {
+        Ok(values.into_iter().flatten().collect())
+    } else {
+        Err(de::Error::custom(
+            errors
+                .into_iter()
+                .filter_map(Result::err)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ))
+    }
+}
+
+pub fn deserialize_vec_url<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_vec_checked(deserializer, "screenshots", |url| {
+        check::url(&url).map(|_| url)
+    })
+}
+/// Deserialize paths to images, which should already be resolved against the root of the project.
+pub fn deserialize_vec_image_path<'de, D>(deserializer: D) -> Result<Vec<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_vec_checked(deserializer, "newScreenshotPaths", |path| {
+        let path = PathBuf::from(path);
+        check::image(&path).map(|_| path)
+    })
+}
+
+pub fn deserialize_some_email<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_checked(deserializer, check::email).map(Some)
+}
+pub fn deserialize_some_country<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_checked(deserializer, check::country).map(Some)
+}
+pub fn deserialize_some_birthday<'de, D>(deserializer: D) -> Result<Option<Date>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date = Date::deserialize(deserializer)?;
+    check::birthday(&date)
+        .map(|_| Some(date))
+        .map_err(de::Error::custom)
+}
+
+/// Check the zip code in `input` against `country`.
+///
+/// This cannot be done while deserializing the profile since the country may be set in another
+/// file, so the document is walked again to point the error at `zipCode`.
+pub fn check_zip_code(input: &str, country: &str) -> Result<(), TomlError> {
+    struct ZipCode<'a>(&'a str);
+    impl<'de> DeserializeSeed<'de> for ZipCode<'_> {
+        type Value = ();
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserialize_checked(deserializer, |zip_code| check::zip_code(zip_code, self.0))
+                .map(|_| ())
+        }
+    }
+    impl<'de> Visitor<'de> for ZipCode<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a table")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            while let Some(key) = map.next_key::<String>()? {
+                if key == "zipCode" {
+                    map.next_value_seed(ZipCode(self.0))?;
+                } else {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    toml_edit::de::Deserializer::parse(input)
+        .and_then(|deserializer| {
+            deserializer.deserialize_struct("ZipCode", &["zipCode"], ZipCode(country))
+        })
+        .map_err(TomlError::from)
+}
+
+pub fn deserialize_non_empty_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let string = String::deserialize(deserializer)?;
+    if string.is_empty() {
+        Err(<D::Error as serde::de::Error>::custom(ERROR))
+    } else {
+        Ok(string)
+    }
+}
+/// [deserialize_non_empty_string] for fields that may be left out.
+pub fn deserialize_some_non_empty_string<'de, D>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_non_empty_string(deserializer).map(Some)
+}
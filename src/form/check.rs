@@ -0,0 +1,293 @@
+//! Checks for the values of form fields, returning a message describing what is wrong
+
+use {
+    chrono::{Datelike, Utc},
+    reqwest::Url,
+    std::{
+        fs::File,
+        io::{ErrorKind, Read},
+        path::Path,
+    },
+    toml_edit::Date,
+};
+
+/// Nobody older than this is expected to be filling in the form.
+const MAX_AGE: u16 = 100;
+
+/// ISO 3166-1 alpha-2 country codes.
+pub const COUNTRIES: &[&str] = &[
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// Postal code formats by country, where `#` is a digit, `A` is a letter, `*` is either and spaces
+/// are optional.
+///
+/// Countries not in here accept any postal code.
+const ZIP_CODES: &[(&str, &[&str])] = &[
+    ("AT", &["####"]),
+    ("AU", &["####"]),
+    ("BE", &["####"]),
+    ("BG", &["####"]),
+    ("BR", &["#####-###", "########"]),
+    ("CA", &["A#A #A#"]),
+    ("CH", &["####"]),
+    ("CN", &["######"]),
+    ("CZ", &["### ##"]),
+    ("DE", &["#####"]),
+    ("DK", &["####"]),
+    ("ES", &["#####"]),
+    ("FI", &["#####"]),
+    ("FR", &["#####"]),
+    (
+        "GB",
+        &[
+            "A# #AA", "A## #AA", "AA# #AA", "AA## #AA", "A#A #AA", "AA#A #AA",
+        ],
+    ),
+    ("GR", &["### ##"]),
+    ("HU", &["####"]),
+    ("IE", &["A** ****"]),
+    ("IN", &["### ###"]),
+    ("IT", &["#####"]),
+    ("JP", &["###-####", "#######"]),
+    ("KR", &["#####"]),
+    ("MX", &["#####"]),
+    ("MY", &["#####"]),
+    ("NL", &["#### AA"]),
+    ("NO", &["####"]),
+    ("NZ", &["####"]),
+    ("PH", &["####"]),
+    ("PK", &["#####"]),
+    ("PL", &["##-###"]),
+    ("PT", &["####-###"]),
+    ("RU", &["######"]),
+    ("SE", &["### ##"]),
+    ("SG", &["######"]),
+    ("SK", &["### ##"]),
+    ("TH", &["#####"]),
+    ("TR", &["#####"]),
+    ("US", &["#####", "#####-####"]),
+    ("ZA", &["####"]),
+];
+
+/// Magic numbers of the image formats that can be uploaded.
+const IMAGES: &[&[u8]] = &[
+    b"\x89PNG\r\n\x1a\n",
+    b"\xff\xd8\xff",
+    b"GIF87a",
+    b"GIF89a",
+    b"BM",
+];
+
+/// An absolute http(s) url.
+pub fn url(url: &str) -> Result<(), String> {
+    Url::parse(url)
+        .map_err(|error| format!("invalid url `{url}`: {error}"))
+        .and_then(|parsed| {
+            if !matches!(parsed.scheme(), "http" | "https") {
+                Err(format!(
+                    "url `{url}` should start with `http://` or `https://`"
+                ))
+            } else if parsed.host_str().is_none_or(str::is_empty) {
+                Err(format!("url `{url}` has no host"))
+            } else {
+                Ok(())
+            }
+        })
+}
+
+pub fn email(email: &str) -> Result<(), String> {
+    email
+        .split_once('@')
+        .filter(|(user, domain)| {
+            !user.is_empty()
+                && !domain.contains('@')
+                && domain
+                    .split_once('.')
+                    .is_some_and(|(name, tld)| !name.is_empty() && !tld.is_empty())
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace)
+        })
+        .map(|_| ())
+        .ok_or_else(|| format!("`{email}` is not an email address"))
+}
+
+pub fn country(country: &str) -> Result<(), String> {
+    if COUNTRIES.contains(&country) {
+        Ok(())
+    } else {
+        Err(format!(
+            "`{country}` is not an ISO 3166-1 alpha-2 country code, such as `US` or `GB`"
+        ))
+    }
+}
+
+/// Check that `zip_code` follows the format of `country`.
+pub fn zip_code(zip_code: &str, country: &str) -> Result<(), String> {
+    fn matches(pattern: &str, zip_code: &str) -> bool {
+        let mut zip_code = zip_code.chars().peekable();
+        pattern.chars().all(|pattern| match pattern {
+            ' ' => {
+                zip_code.next_if_eq(&' ');
+                true
+            }
+            '#' => zip_code.next().is_some_and(|char| char.is_ascii_digit()),
+            'A' => zip_code
+                .next()
+                .is_some_and(|char| char.is_ascii_alphabetic()),
+            '*' => zip_code
+                .next()
+                .is_some_and(|char| char.is_ascii_alphanumeric()),
+            pattern => zip_code.next() == Some(pattern),
+        }) && zip_code.next().is_none()
+    }
+
+    match ZIP_CODES.iter().find(|(code, _)| *code == country) {
+        Some((_, patterns)) if !patterns.iter().any(|pattern| matches(pattern, zip_code)) => {
+            Err(format!(
+                "`{zip_code}` is not a valid zip code for `{country}`, expected {}",
+                patterns
+                    .iter()
+                    .map(|pattern| format!("`{pattern}`"))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A readable file that starts like an image.
+pub fn image(path: &Path) -> Result<(), String> {
+    let mut header = Vec::with_capacity(12);
+    File::open(path)
+        .and_then(|file| file.take(12).read_to_end(&mut header))
+        .map_err(|error| match error.kind() {
+            ErrorKind::NotFound => format!("`{}` does not exist", path.display()),
+            _ => format!("`{}` cannot be read: {error}", path.display()),
+        })?;
+
+    if IMAGES.iter().any(|magic| header.starts_with(magic))
+        || (header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WEBP"))
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "`{}` is not a png, jpeg, gif, webp or bmp image",
+            path.display()
+        ))
+    }
+}
+
+/// A birthday in the past, no more than [MAX_AGE] years ago.
+pub fn birthday(birthday: &Date) -> Result<(), String> {
+    let today = Utc::now().date_naive();
+    let birthday_tuple = (birthday.year, birthday.month, birthday.day);
+    let today_tuple = (
+        u16::try_from(today.year()).unwrap_or_default(),
+        u8::try_from(today.month()).unwrap_or_default(),
+        u8::try_from(today.day()).unwrap_or_default(),
+    );
+
+    if birthday_tuple >= today_tuple {
+        Err(format!("birthday `{birthday}` is not in the past"))
+    } else if birthday_tuple
+        < (
+            today_tuple.0.saturating_sub(MAX_AGE),
+            today_tuple.1,
+            today_tuple.2,
+        )
+    {
+        Err(format!(
+            "birthday `{birthday}` is more than {MAX_AGE} years ago"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        url("https://github.com/asdfish/neighborhood_cli").unwrap();
+        url("http://localhost:8080/demo").unwrap();
+        assert!(url("github.com/asdfish/neighborhood_cli").is_err());
+        assert!(url("ftp://example.com").is_err());
+        assert!(url("file:///home/me/screenshot.png").is_err());
+
+        email("me@example.com").unwrap();
+        [
+            "me",
+            "@example.com",
+            "me@example",
+            "me@@example.com",
+            "me @example.com",
+        ]
+        .into_iter()
+        .for_each(|invalid| assert!(email(invalid).is_err(), "{invalid}"));
+
+        country("US").unwrap();
+        assert!(country("USA").is_err());
+        assert!(country("us").is_err());
+
+        zip_code("12345", "US").unwrap();
+        zip_code("12345-6789", "US").unwrap();
+        zip_code("K1A 0B1", "CA").unwrap();
+        zip_code("K1A0B1", "CA").unwrap();
+        zip_code("SW1A 1AA", "GB").unwrap();
+        zip_code("1234 AB", "NL").unwrap();
+        zip_code("anything", "AQ").unwrap();
+        assert!(zip_code("1234", "US").is_err());
+        assert!(zip_code("123456", "US").is_err());
+        assert!(zip_code("12345", "CA").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let png = dir.path().join("screenshot.png");
+        std::fs::write(&png, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        image(&png).unwrap();
+        image(dir.path()).unwrap_err();
+        image(Path::new("src/main.rs")).unwrap_err();
+        image(Path::new("does/not/exist.png")).unwrap_err();
+
+        birthday(&Date {
+            year: 2000,
+            month: 1,
+            day: 1,
+        })
+        .unwrap();
+        assert!(
+            birthday(&Date {
+                year: 9999,
+                month: 1,
+                day: 1
+            })
+            .is_err()
+        );
+        assert!(
+            birthday(&Date {
+                year: 1800,
+                month: 1,
+                day: 1
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Local records of everything that has been posted
+
+use {
+    crate::{
+        MainError,
+        cache::HISTORY,
+        config::Config,
+        crypt, logger,
+        outbox::{Media, Source},
+    },
+    chrono::{DateTime, Utc},
+    serde::{Deserialize, Serialize, de::DeserializeOwned},
+    serde_json::Value,
+    sha2::{Digest, Sha256},
+    std::{
+        borrow::Cow,
+        fs::{self, File},
+        io::Read,
+        path::{Path, PathBuf},
+    },
+};
+
+/// A release that was shipped.
+#[derive(Deserialize, Serialize)]
+pub struct Release {
+    pub time: DateTime<Utc>,
+    /// The message the backend responded with.
+    pub response: String,
+    /// What was sent to the backend, without the token.
+    pub payload: Value,
+}
+impl Release {
+    /// Remove secrets from `payload`, and personal information if `redact` is set.
+    pub fn new<T>(payload: &T, response: String, redact: bool) -> Self
+    where
+        T: Serialize,
+    {
+        let mut payload = serde_json::to_value(payload).unwrap_or_default();
+        if let Value::Object(object) = &mut payload {
+            object.remove("token");
+        }
+        if redact {
+            logger::redact_value(&mut payload);
+        }
+
+        Self {
+            time: Utc::now(),
+            response,
+            payload,
+        }
+    }
+}
+
+/// A video of a devlog.
+#[derive(Deserialize, Serialize)]
+pub struct Video {
+    /// The file that was uploaded, unless the video was given as a url.
+    pub path: Option<PathBuf>,
+    pub url: String,
+    /// The sha256 of the file when it was uploaded, to tell which local file it was.
+    pub sha256: Option<String>,
+}
+impl Video {
+    pub fn new(source: &Source, url: String) -> Self {
+        match source {
+            Source::File(Media { path, sha256 }) => Self {
+                path: Some(path.clone()),
+                url,
+                sha256: Some(sha256.clone()),
+            },
+            Source::Url(_) => Self {
+                path: None,
+                url,
+                sha256: None,
+            },
+        }
+    }
+}
+
+/// A devlog that was posted.
+#[derive(Deserialize, Serialize)]
+pub struct Devlog {
+    pub time: DateTime<Utc>,
+    pub message: String,
+    /// The message the backend responded with.
+    pub response: String,
+    pub demo: Option<Video>,
+    pub photobooth: Option<Video>,
+}
+
+pub fn sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => break,
+            read => hasher.update(&buffer[..read]),
+        }
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Get an entry by its number, starting from 1.
+pub fn nth<T>(entries: &[T], n: usize) -> Option<&T> {
+    n.checked_sub(1).and_then(|i| entries.get(i))
+}
+
+fn dir(project: &str, kind: &str) -> Result<PathBuf, MainError> {
+    HISTORY
+        .as_ref()
+        .ok_or(MainError::GetCache)
+        .map(|history| history.join(project).join(kind))
+}
+
+fn record<T>(
+    project: &str,
+    kind: &str,
+    time: DateTime<Utc>,
+    entry: &T,
+    config: &Config,
+) -> Result<(), MainError>
+where
+    T: Serialize,
+{
+    let mut path = dir(project, kind)?;
+    path.push(time.timestamp_millis().to_string());
+    path.set_extension("json");
+
+    let entry = serde_json::to_string_pretty(entry).expect("history entries should serialize");
+    crypt::write(Cow::Owned(path), &entry, config)
+}
+
+/// Read every entry of `kind`, from oldest to newest.
+fn read<T>(project: &str, kind: &str, config: &Config) -> Result<Vec<T>, MainError>
+where
+    T: DeserializeOwned,
+{
+    let dir = dir(project, kind)?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = fs::read_dir(&dir)
+        .map_err(|error| MainError::ReadDirectory(error, Cow::Owned(dir.clone())))?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<i64>().ok())
+                .map(|time| (time, path))
+        })
+        .collect::<Vec<_>>();
+    paths.sort_unstable_by_key(|(time, _)| *time);
+
+    paths
+        .into_iter()
+        .map(|(_, path)| {
+            crypt::read(&path, config).and_then(|entry| {
+                serde_json::from_str(&entry)
+                    .map_err(|error| MainError::ParseHistory(error, Cow::Owned(path)))
+            })
+        })
+        .collect()
+}
+
+pub fn record_release(project: &str, release: &Release, config: &Config) -> Result<(), MainError> {
+    record(project, "releases", release.time, release, config)
+}
+pub fn releases(project: &str, config: &Config) -> Result<Vec<Release>, MainError> {
+    read(project, "releases", config)
+}
+pub fn record_devlog(project: &str, devlog: &Devlog, config: &Config) -> Result<(), MainError> {
+    record(project, "devlogs", devlog.time, devlog, config)
+}
+pub fn devlogs(project: &str, config: &Config) -> Result<Vec<Devlog>, MainError> {
+    read(project, "devlogs", config)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn redaction() {
+        let payload = json!({ "token": "secret", "email": "me@example.com", "appId": "app" });
+
+        let release = Release::new(&payload, String::new(), false);
+        assert_eq!(release.payload.get("token"), None);
+        assert_eq!(release.payload["email"], "me@example.com");
+
+        let release = Release::new(&payload, String::new(), true);
+        assert_eq!(release.payload.get("token"), None);
+        assert_ne!(release.payload["email"], "me@example.com");
+        assert_eq!(release.payload["appId"], "app");
+    }
+
+    #[test]
+    fn file_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("demo.mp4");
+        fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            sha256(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(sha256(&dir.path().join("missing.mp4")).is_err());
+    }
+}
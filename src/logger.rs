@@ -0,0 +1,135 @@
+//! Logging to stderr with redaction of credentials and personal information
+
+use {
+    log::{Level, LevelFilter, Log, Metadata, Record},
+    reqwest::Url,
+    serde_json::Value,
+    std::borrow::Cow,
+};
+
+/// Keys whose values should never end up in logs.
+const REDACTED_KEYS: &[&str] = &[
+    "token",
+    "neighbor",
+    "otp",
+    "email",
+    "firstName",
+    "lastName",
+    "addressLine1",
+    "addressLine2",
+    "city",
+    "stateProvince",
+    "zipCode",
+    "birthday",
+];
+const REDACTED: &str = "<redacted>";
+
+struct Logger;
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // dependencies such as `hyper` are very noisy at the trace level
+        metadata.target().starts_with(crate::NAME) && metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger, with `verbosity` being the amount of times `-v` was passed.
+pub fn init(verbosity: u8) {
+    static LOGGER: Logger = Logger;
+
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(match verbosity {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        });
+    }
+}
+
+pub fn enabled(level: Level) -> bool {
+    level <= log::max_level()
+}
+
+/// Replace the values of sensitive query parameters.
+pub fn redact_url(url: &Url) -> Cow<'_, str> {
+    if !url
+        .query_pairs()
+        .any(|(key, _)| REDACTED_KEYS.contains(&key.as_ref()))
+    {
+        return Cow::Borrowed(url.as_str());
+    }
+
+    let mut redacted = url.clone();
+    redacted
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(url.query_pairs().map(|(key, value)| {
+            if REDACTED_KEYS.contains(&key.as_ref()) {
+                (key, Cow::Borrowed(REDACTED))
+            } else {
+                (key, value)
+            }
+        }));
+
+    Cow::Owned(redacted.into())
+}
+
+/// Replace the values of personal or secret keys.
+pub fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(object) => object.iter_mut().for_each(|(key, value)| {
+            if REDACTED_KEYS.contains(&key.as_str()) {
+                *value = Value::String(REDACTED.to_string());
+            } else {
+                redact_value(value);
+            }
+        }),
+        Value::Array(array) => array.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Redact a request or response body, bodies that are not json are logged as is.
+pub fn redact_body(body: &[u8]) -> Cow<'_, str> {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            Cow::Owned(value.to_string())
+        }
+        Err(_) => String::from_utf8_lossy(body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redaction() {
+        assert_eq!(
+            redact_url(
+                &Url::parse("https://neighborhood.hackclub.com/api/getUserApps?token=secret")
+                    .unwrap()
+            ),
+            "https://neighborhood.hackclub.com/api/getUserApps?token=%3Credacted%3E"
+        );
+        assert_eq!(
+            redact_url(&Url::parse("https://neighborhood.hackclub.com/api/signup").unwrap()),
+            "https://neighborhood.hackclub.com/api/signup"
+        );
+        assert_eq!(
+            redact_body(
+                br#"{"token":"secret","codeUrl":"https://github.com","nested":[{"birthday":"2000-01-01"}]}"#
+            ),
+            r#"{"codeUrl":"https://github.com","nested":[{"birthday":"<redacted>"}],"token":"<redacted>"}"#
+        );
+        assert_eq!(redact_body(b"not json"), "not json");
+    }
+}
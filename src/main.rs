@@ -1,14 +1,27 @@
 mod api;
+mod autofill;
 mod cache;
+mod config;
+mod crypt;
+mod editor;
 mod env;
+mod form;
+mod history;
+mod logger;
+mod outbox;
+mod profile;
+mod schema;
 mod subcommand;
 
 use {
     cfg_if::cfg_if,
     clap::{
         ArgAction,
-        builder::{Arg, Command, NonEmptyStringValueParser},
+        builder::{Arg, ArgGroup, Command, NonEmptyStringValueParser, PathBufValueParser},
+        value_parser,
     },
+    clap_complete::{ArgValueCandidates, CompleteEnv},
+    reqwest::StatusCode,
     std::{
         borrow::Cow,
         fmt::{self, Display, Formatter, Write},
@@ -22,11 +35,99 @@ use {
 const NAME: &str = "neighborhood_cli";
 const VERSION: &str = "1.0.1";
 
+/// Flags used by [editor::Editor::from_args].
+fn editor_args() -> [Arg; 2] {
+    [
+        Arg::new("editor")
+            .short('E')
+            .long("editor")
+            .help("The editor used for editing the form, which may include arguments such as `code --wait`. If this is unset, `VISUAL`, `EDITOR` and the `editor` config key will be used instead, falling back to `nano` or `vi`")
+            .value_parser(NonEmptyStringValueParser::default()),
+        Arg::new("arg")
+            .short('a')
+            .long("arg")
+            .help("An argument passed to the editor, can be passed multiple times. If this is unset, the `editor-args` config key will be used instead")
+            .value_parser(NonEmptyStringValueParser::default())
+            .allow_hyphen_values(true)
+            .action(ArgAction::Append),
+    ]
+}
+/// The arguments of a devlog, shared with its drafts.
+fn devlog_args() -> [Arg; 7] {
+    let [editor, arg] = editor_args();
+    [
+        editor,
+        arg,
+        Arg::new("from-git")
+            .long("from-git")
+            .value_name("since-ref")
+            .help("Start the message from the subjects of the commits since <since-ref>, or since the last devlog of this project, and open the editor to refine it")
+            .value_parser(NonEmptyStringValueParser::default())
+            .num_args(0..=1),
+        Arg::new("photobooth")
+            .help("The path to a video explaining what you did")
+            .short('p')
+            .long("photobooth")
+            .value_name("path")
+            .value_parser(NonEmptyStringValueParser::default())
+            .group("videos"),
+        Arg::new("photobooth-url")
+            .help("The url of an already uploaded video explaining what you did, instead of `--photobooth`")
+            .long("photobooth-url")
+            .value_name("url")
+            .value_parser(url_value_parser)
+            .conflicts_with("photobooth")
+            .group("videos"),
+        Arg::new("demo")
+            .help("The path to a video showcasing your product")
+            .short('d')
+            .long("demo")
+            .value_name("path")
+            .value_parser(NonEmptyStringValueParser::default())
+            .group("videos"),
+        Arg::new("demo-url")
+            .help("The url of an already uploaded video showcasing your product, such as the demo of a previous devlog, instead of `--demo`")
+            .long("demo-url")
+            .value_name("url")
+            .value_parser(url_value_parser)
+            .conflicts_with("demo")
+            .group("videos"),
+    ]
+}
+/// Either video of a devlog can be left out, but not both.
+fn videos_group() -> ArgGroup {
+    ArgGroup::new("videos").multiple(true).required(true)
+}
+fn url_value_parser(url: &str) -> Result<String, String> {
+    form::check::url(url).map(|_| url.to_string())
+}
+fn json_arg() -> Arg {
+    Arg::new("json")
+        .long("json")
+        .help("Print json instead of text")
+        .action(ArgAction::SetTrue)
+}
+fn no_confirm_arg() -> Arg {
+    Arg::new("no-confirm")
+        .short('y')
+        .long("no-confirm")
+        .help("Disable prompts, answering yes to them all. Can also be set with `NEIGHBORHOOD_NO_CONFIRM` or the `no-confirm` config key")
+        .action(ArgAction::SetTrue)
+}
+
 fn root_command() -> Command {
     Command::new(NAME)
         .about("Cli for the hackclub's neighborhood event")
         .version(VERSION)
         .subcommand_required(true)
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Log requests, pass twice to also log their bodies with credentials and personal information redacted")
+                .action(ArgAction::Count)
+                .global(true),
+        )
         .subcommand(
             Command::new("auth")
                 .about("Login/signup into neighborhood")
@@ -53,6 +154,120 @@ fn root_command() -> Command {
                         ),
                 ),
         )
+        .subcommand(
+            Command::new("completions")
+                .about("Print a script that registers shell completions")
+                .arg(
+                    Arg::new("shell")
+                        .help("The shell to generate completions for")
+                        .value_parser(["bash", "elvish", "fish", "powershell", "zsh"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("queue")
+                .about("Manage the updates that were queued while the network was down")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List the queued updates, starting from the oldest one"))
+                .subcommand(Command::new("flush").about("Send the queued updates in order, stopping at the first one that fails"))
+                .subcommand(
+                    Command::new("drop")
+                        .about("Remove an update from the queue without sending it")
+                        .arg(
+                            Arg::new("n")
+                                .help("The number of the update, as shown by `list`")
+                                .value_parser(value_parser!(usize))
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Print the JSON schema of a form")
+                .arg(
+                    Arg::new("form")
+                        .help("The form to print the schema of")
+                        .value_parser(["profile", "release"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Read and write configuration")
+                .long_about("Read and write configuration\n\nValues are taken from flags, then environment variables, then the repository config (`.neighborhood.toml` in the current directory or any of its parents), then the user config")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("get")
+                        .about("Print the value of a key")
+                        .arg(
+                            Arg::new("key")
+                                .help("The key to read, one of `config list` or `vars.<name>`")
+                                .value_parser(config::parse_key)
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("Set the value of a key in the user config")
+                        .arg(
+                            Arg::new("key")
+                                .help("The key to write, one of `config list` or `vars.<name>`")
+                                .value_parser(config::parse_key)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("value")
+                                .help("The value, parsed as toml and falling back to a string")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("repo")
+                                .long("repo")
+                                .help("Write to the repository config instead of the user config")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(Command::new("list").about("Print every key, their values and where they were set")),
+        )
+        .subcommand(
+            Command::new("encrypt")
+                .about("Encrypt the cached files that were saved before encryption was enabled"),
+        )
+        .subcommand(
+            Command::new("docs")
+                .about("Generate man pages or a markdown reference for this cli")
+                .hide(true)
+                .arg(
+                    Arg::new("format")
+                        .help("The format of the generated documentation")
+                        .value_parser(["man", "markdown"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("out-dir")
+                        .help("The directory the documentation will be written to")
+                        .value_parser(PathBufValueParser::new())
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("profile")
+                .about("Manage the personal information shared between all of your projects")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("edit")
+                        .about("Edit your profile")
+                        .args(editor_args())
+                        .arg(
+                            Arg::new("reset")
+                                .short('r')
+                                .long("reset")
+                                .help("If set, this will reset your profile")
+                                .action(ArgAction::SetTrue),
+                        )
+                        .arg(no_confirm_arg()),
+                ),
+        )
         .subcommand(
             Command::new("project")
                 .about("Manipulate projects")
@@ -60,58 +275,159 @@ fn root_command() -> Command {
                     Arg::new("name")
                         .help("The name of this project")
                         .value_parser(NonEmptyStringValueParser::default())
+                        .add(ArgValueCandidates::new(cache::project_names))
                         .required(true)
                 )
                 .subcommand_required(true)
+                .subcommand(
+                    Command::new("releases")
+                        .about("Look back at the releases that have been shipped")
+                        .subcommand_required(true)
+                        .subcommand(Command::new("list").about("List the releases, starting from the first one"))
+                        .subcommand(
+                            Command::new("show")
+                                .about("Show what was sent in a release")
+                                .arg(
+                                    Arg::new("n")
+                                        .help("The number of the release, as shown by `list`")
+                                        .value_parser(value_parser!(usize))
+                                        .required(true)
+                                )
+                        )
+                        .subcommand(
+                            Command::new("diff")
+                                .about("Show the fields that changed between two releases")
+                                .arg(
+                                    Arg::new("a")
+                                        .help("The number of the older release")
+                                        .value_parser(value_parser!(usize))
+                                        .required(true)
+                                )
+                                .arg(
+                                    Arg::new("b")
+                                        .help("The number of the newer release")
+                                        .value_parser(value_parser!(usize))
+                                        .required(true)
+                                )
+                        )
+                )
+                .subcommand(
+                    Command::new("devlogs")
+                        .about("Look back at the devlogs that have been posted")
+                        .subcommand_required(true)
+                        .subcommand(
+                            Command::new("list")
+                                .about("List the devlogs, starting from the first one")
+                                .arg(json_arg())
+                        )
+                        .subcommand(
+                            Command::new("show")
+                                .about("Show a devlog with the urls of its videos")
+                                .arg(
+                                    Arg::new("n")
+                                        .help("The number of the devlog, as shown by `list`")
+                                        .value_parser(value_parser!(usize))
+                                        .required(true)
+                                )
+                                .arg(json_arg())
+                        )
+                )
+                .subcommand(
+                    Command::new("screenshots")
+                        .about("Manage the screenshots of the cached release form")
+                        .subcommand_required(true)
+                        .subcommand(Command::new("list").about("List the screenshots and their numbers"))
+                        .subcommand(
+                            Command::new("add")
+                                .about("Add screenshots, uploading any paths and adding urls as is")
+                                .arg(
+                                    Arg::new("screenshots")
+                                        .value_name("path|url")
+                                        .value_parser(NonEmptyStringValueParser::default())
+                                        .num_args(1..)
+                                        .required(true)
+                                )
+                        )
+                        .subcommand(
+                            Command::new("remove")
+                                .about("Remove screenshots")
+                                .arg(
+                                    Arg::new("screenshots")
+                                        .value_name("number|url")
+                                        .value_parser(NonEmptyStringValueParser::default())
+                                        .num_args(1..)
+                                        .required(true)
+                                )
+                        )
+                        .subcommand(
+                            Command::new("reorder")
+                                .about("Move screenshots to the front in the given order, the rest keep their order after them")
+                                .arg(
+                                    Arg::new("order")
+                                        .value_name("number|url")
+                                        .value_parser(NonEmptyStringValueParser::default())
+                                        .num_args(1..)
+                                        .required(true)
+                                )
+                        )
+                )
                 .subcommand(
                     Command::new("update")
                         .about("Update things related to this project")
                         .subcommand_required(true)
                         .arg(
                             Arg::new("message")
-                                .help("What changed between this and the last version")
+                                .help("What changed between this and the last version, `-` reads it from stdin. If neither this, `--message` nor `--message-file` is set, an editor is opened to write it")
+                                .value_parser(NonEmptyStringValueParser::default())
+                        )
+                        .arg(
+                            Arg::new("message-flag")
+                                .short('m')
+                                .long("message")
+                                .value_name("message")
+                                .help("Same as <message>, `-` reads it from stdin")
                                 .value_parser(NonEmptyStringValueParser::default())
+                                .allow_hyphen_values(true)
+                                .conflicts_with("message")
+                        )
+                        .arg(
+                            Arg::new("message-file")
+                                .long("message-file")
+                                .value_name("path")
+                                .help("Read the message from a file, such as a markdown file")
+                                .value_parser(PathBufValueParser::new())
+                                .conflicts_with_all(["message", "message-flag"])
                         )
                         .subcommand(
                             Command::new("devlog")
                                 .about("Post a devlog")
-                                .arg(
-                                    Arg::new("photobooth")
-                                        .help("The path to a video explaining what you did")
-                                        .short('p')
-                                        .long("photobooth")
-                                        .value_name("path")
-                                        .value_parser(NonEmptyStringValueParser::default())
-                                        .required(true),
+                                .args(devlog_args())
+                                .group(videos_group())
+                                .args_conflicts_with_subcommands(true)
+                                .subcommand_negates_reqs(true)
+                                .subcommand(
+                                    Command::new("draft")
+                                        .about("Save a devlog to post later, such as when you are offline")
+                                        .args(devlog_args())
+                                        .group(videos_group())
                                 )
-                                .arg(
-                                    Arg::new("demo")
-                                        .help("The path to a video showcasing your product")
-                                        .short('d')
-                                        .long("demo")
-                                        .value_name("path")
-                                        .value_parser(NonEmptyStringValueParser::default())
-                                        .required(true),
+                                .subcommand(Command::new("drafts").about("List the saved drafts"))
+                                .subcommand(
+                                    Command::new("post")
+                                        .about("Upload the videos of a draft and post it")
+                                        .arg(
+                                            Arg::new("draft")
+                                                .help("The number of the draft, as shown by `drafts`")
+                                                .value_parser(value_parser!(usize))
+                                                .required(true)
+                                        )
                                 )
                         )
                         .subcommand(
                             Command::new("release")
                                 .visible_alias("ship")
                                 .about("Post a new release")
-                                .arg(
-                                    Arg::new("editor")
-                                        .short('E')
-                                        .long("editor")
-                                        .help("The editor used for editing the form. If this is unset, `VISUAL` and `EDITOR` will bve used instead and will raise an error if those are also unset")
-                                        .value_parser(NonEmptyStringValueParser::default())
-                                )
-                                .arg(
-                                    Arg::new("arg")
-                                        .short('a')
-                                        .long("arg")
-                                        .help("Arguments passed to the editor")
-                                        .value_parser(NonEmptyStringValueParser::default())
-                                )
+                                .args(editor_args())
                                 .arg(
                                     Arg::new("edit")
                                         .short('e')
@@ -127,20 +443,42 @@ fn root_command() -> Command {
                                         .action(ArgAction::SetTrue)
                                         .requires("edit")
                                 )
+                                .arg(no_confirm_arg())
                                 .arg(
-                                    Arg::new("no-confirm")
-                                        .short('y')
-                                        .long("no-confirm")
-                                        .help("Disable prompts, answering yes to them all")
+                                    Arg::new("skip-link-check")
+                                        .long("skip-link-check")
+                                        .help("If set, the links of the release will not be checked before shipping it")
                                         .action(ArgAction::SetTrue)
                                 )
+                                .arg(
+                                    Arg::new("set")
+                                        .short('s')
+                                        .long("set")
+                                        .value_name("key=value")
+                                        .help("Set a field of the form without opening the editor. Can be passed multiple times and can also be done with `NEIGHBORHOOD_RELEASE_<FIELD>` environment variables, such as `NEIGHBORHOOD_RELEASE_PLAYABLE_URL`")
+                                        .value_parser(NonEmptyStringValueParser::default())
+                                        .action(ArgAction::Append)
+                                )
+                                .arg(
+                                    Arg::new("from-file")
+                                        .short('f')
+                                        .long("from-file")
+                                        .value_name("path")
+                                        .help("Set fields of the form from a toml or json file. `NEIGHBORHOOD_RELEASE_<FIELD>` and `--set` take precedence over this")
+                                        .value_parser(PathBufValueParser::new())
+                                )
                         )
                 )
         )
 }
 
 fn main() -> ExitCode {
-    match subcommand::execute(root_command().get_matches()) {
+    CompleteEnv::with_factory(root_command).complete();
+
+    let args = root_command().get_matches();
+    logger::init(args.get_count("verbose"));
+
+    match subcommand::execute(args) {
         Ok(()) => ExitCode::SUCCESS,
         Err(error) => {
             eprintln!("{error}");
@@ -161,17 +499,54 @@ pub enum MainError {
     ExecuteCommand(io::Error, String),
     ReadLine(io::Error),
     GetCache,
+    GetConfig,
     GetToken,
     GetMetadata(io::Error, Cow<'static, Path>),
+    InvalidToken(Option<String>),
     NoEditor,
+    ParseEditor(String),
+    EmptyMessage,
+    UnknownGitRef(String),
+    IncompleteProfile(&'static str),
+    InvalidZipCode(String),
+    NoReleaseForm(String),
+    ScreenshotsNotArray,
+    UnknownScreenshot(String),
+    InvalidScreenshot(String),
+    BrokenLinks(usize),
     NonExistantProject(String),
+    UnknownConfigKey(String),
+    UserConfigKey(String, Cow<'static, Path>),
+    UnsetConfigKey(String),
+    ParseConfig(TomlError, Cow<'static, Path>),
+    ParseProfile(TomlError),
     ParseReleaseConfig(TomlError),
+    ParseReleaseOverrides(String, Cow<'static, Path>),
+    InvalidReleaseOverride(String),
+    UnknownReleaseField(String),
     RemoveFile(io::Error, Cow<'static, Path>),
+    ReadDirectory(io::Error, Cow<'static, Path>),
     ReadFile(io::Error, Cow<'static, Path>),
+    ParseHistory(serde_json::Error, Cow<'static, Path>),
+    Decrypt(Cow<'static, Path>),
+    EmptySecret,
+    GenerateRandom(getrandom::Error),
+    PassphraseMismatch,
+    EncryptionDisabled,
+    SecretMismatch(Cow<'static, Path>),
+    UnknownRelease(usize),
+    UnknownDevlog(usize),
+    ParseDraft(serde_json::Error, Cow<'static, Path>),
+    UnknownDraft(usize),
+    ParseQueued(serde_json::Error, Cow<'static, Path>),
+    QueuedFileChanged(Cow<'static, Path>),
+    QueueStuck(Box<MainError>),
+    UnknownQueued(usize),
     SetPermissions(io::Error, Cow<'static, Path>),
     WriteFile(io::Error, Cow<'static, Path>),
+    WriteStdout(io::Error),
     ExecuteRequest(reqwest::Error),
-    Server(Option<String>),
+    Server(Option<StatusCode>, Option<String>),
 }
 impl Display for MainError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
@@ -195,9 +570,43 @@ impl Display for MainError {
             Self::ReadLine(error) => write!(f, "failed to read input: {error}"),
             Self::GetMetadata(error, path) => write!(f, "failed to get metadata for path `{}`: {error}", path.display()),
             Self::RemoveFile(error, path) => write!(f, "failed to remove file at path `{}`: {error}", path.display()),
-            Self::NoEditor => f.write_str("failed to get editor: flag `--editor` was not specified and both environment variables `VISUAL` and `EDITOR` were not set"),
+            Self::NoEditor => f.write_str("failed to get editor: flag `--editor` was not specified, the environment variables `VISUAL` and `EDITOR` and the `editor` config key were not set and neither `nano` nor `vi` could be found"),
+            Self::ParseEditor(editor) => write!(f, "failed to parse editor `{editor}`: unterminated quote or escape"),
+            Self::EmptyMessage => f.write_str("the message should not be empty"),
+            Self::UnknownGitRef(reference) => write!(f, "`{reference}` is not a commit of the git repository in the current directory"),
             Self::NonExistantProject(project) => write!(f, "project `{project}` does not exist"),
-            Self::ParseReleaseConfig(error) => write!(f, "failed to read release config:\n{error}\nRun `neighborhood_cli project <project> post ship -m <message> -e` to edit"),
+            Self::ParseConfig(error, path) => write!(f, "failed to read config at path `{}`:\n{error}", path.display()),
+            Self::UserConfigKey(key, path) => write!(f, "`{key}` can only be set in the user config, as a repository could use it to run commands or send your token elsewhere, remove it from `{}`", path.display()),
+            Self::UnsetConfigKey(key) => write!(f, "`{key}` is not set"),
+            Self::UnknownConfigKey(key) => write!(f, "unknown config key `{key}`, run `neighborhood_cli config list` to see all keys"),
+            Self::IncompleteProfile(field) => write!(f, "`{field}` is not set in your profile or release form, run `neighborhood_cli profile edit` to set it"),
+            Self::BrokenLinks(count) => write!(f, "{count} link(s) of the release are broken, fix them or pass `--skip-link-check` to ship anyway"),
+            Self::NoReleaseForm(project) => write!(f, "project `{project}` does not have a release form yet, run `neighborhood_cli project {project} update <message> release -e` to create it"),
+            Self::ScreenshotsNotArray => f.write_str("`screenshots` in the release form is not an array"),
+            Self::UnknownScreenshot(screenshot) => write!(f, "unknown screenshot `{screenshot}`, run `neighborhood_cli project <project> screenshots list` to see them"),
+            Self::InvalidScreenshot(error) => f.write_str(error),
+            Self::InvalidZipCode(error) => write!(f, "{error}, run `neighborhood_cli profile edit` or set `zipCode` in the release form to fix it"),
+            Self::ParseProfile(error) => write!(f, "failed to read profile:\n{error}\nRun `neighborhood_cli profile edit` to edit"),
+            Self::ParseReleaseOverrides(error, path) => write!(f, "failed to read release fields from path `{}`: {error}", path.display()),
+            Self::InvalidReleaseOverride(set) => write!(f, "invalid value `{set}` for `--set`, expected `key=value`"),
+            Self::UnknownReleaseField(field) => write!(f, "unknown release form field `{field}`"),
+            Self::ParseReleaseConfig(error) => write!(f, "failed to read release config:\n{error}\nRun `neighborhood_cli project <project> update <message> release -e` to edit or set fields with `--set <key>=<value>`"),
+            Self::ReadDirectory(error, path) => write!(f, "failed to read directory at path `{}`: {error}", path.display()),
+            Self::ParseHistory(error, path) => write!(f, "failed to read history entry at path `{}`: {error}", path.display()),
+            Self::Decrypt(path) => write!(f, "failed to decrypt file at path `{}`: the passphrase or key file is wrong or the file is corrupted", path.display()),
+            Self::EmptySecret => f.write_str("the passphrase or key file for encrypted files is empty"),
+            Self::GenerateRandom(error) => write!(f, "failed to generate random bytes: {error}"),
+            Self::PassphraseMismatch => f.write_str("the passphrases do not match"),
+            Self::EncryptionDisabled => f.write_str("encryption is not enabled, set `encryption.enable` to true first"),
+            Self::SecretMismatch(path) => write!(f, "the passphrase or key file is not the one `{}` was encrypted with, every file has to be encrypted with the same one", path.display()),
+            Self::UnknownDevlog(n) => write!(f, "there is no devlog {n}, run `neighborhood_cli project <project> devlogs list` to see them"),
+            Self::ParseDraft(error, path) => write!(f, "failed to read draft at path `{}`: {error}", path.display()),
+            Self::ParseQueued(error, path) => write!(f, "failed to read queued update at path `{}`: {error}", path.display()),
+            Self::QueuedFileChanged(path) => write!(f, "`{}` changed since it was queued, drop the update with `neighborhood_cli queue drop` and post it again", path.display()),
+            Self::QueueStuck(error) => write!(f, "the oldest queued update could not be sent: {error}\nfix it and run `neighborhood_cli queue flush`, or remove it with `neighborhood_cli queue drop 1`"),
+            Self::UnknownQueued(n) => write!(f, "there is no queued update {n}, run `neighborhood_cli queue list` to see them"),
+            Self::UnknownDraft(n) => write!(f, "there is no draft {n}, run `neighborhood_cli project <project> update devlog drafts` to see them"),
+            Self::UnknownRelease(n) => write!(f, "there is no release {n}, run `neighborhood_cli project <project> releases list` to see them"),
             Self::ReadFile(error, path) => write!(
                 f,
                 "failed to read file at path `{}`: {error}",
@@ -209,6 +618,7 @@ impl Display for MainError {
                 "failed to write to file at path `{}`: {error}",
                 path.display()
             ),
+            Self::WriteStdout(error) => write!(f, "failed to write to stdout: {error}"),
             Self::GetCache =>
 {
         f.write_str("failed to get the cache directory, please ensure that you have the following environment variables set:")
@@ -234,10 +644,20 @@ impl Display for MainError {
                         })
                 })
             }
+            Self::GetConfig => f.write_str("failed to get the config directory, please ensure that `HOME` is set or use `--repo`"),
             Self::GetToken => f.write_str("failed to get token, please run `neighborhood_cli auth <EMAIL> send` and `neighborhood_cli auth <EMAIL> login <OTP>` first"),
+            Self::InvalidToken(error) => {
+                f.write_str("your token is invalid or has expired")?;
+                if let Some(error) = error {
+                    write!(f, " ({error})")?;
+                }
+                f.write_str(", please run `neighborhood_cli auth <EMAIL> send` and `neighborhood_cli auth <EMAIL> login <OTP>` again")
+            }
             Self::ExecuteRequest(error) => write!(f, "failed to execute request: {error}"),
-            Self::Server(Some(error)) => write!(f, "the backend responded with an error: {error}"),
-            Self::Server(None) => f.write_str("the backend responded with an unknown error"),
+            Self::Server(Some(status), Some(error)) => write!(f, "the backend responded with status `{status}`: {error}"),
+            Self::Server(Some(status), None) => write!(f, "the backend responded with status `{status}`"),
+            Self::Server(None, Some(error)) => write!(f, "the backend responded with an error: {error}"),
+            Self::Server(None, None) => f.write_str("the backend responded with an unknown error"),
         }
     }
 }
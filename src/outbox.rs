@@ -0,0 +1,355 @@
+//! Updates that could not be sent because the network was down, replayed in order later
+//!
+//! Operations are only queued once they have been validated, so that replaying them does not need
+//! any input. The files they upload are hashed when they are queued, and replaying stops if a file
+//! changed in the meantime.
+
+use {
+    crate::{MainError, api, cache::OUTBOX, config::Config, crypt, history},
+    chrono::{DateTime, Utc},
+    reqwest::Client,
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+    std::{
+        borrow::Cow,
+        fs,
+        path::{Path, PathBuf},
+        thread,
+        time::Duration,
+    },
+    tokio::runtime,
+};
+
+/// How many times an operation is tried before giving up, when the network is down.
+const ATTEMPTS: u32 = 3;
+/// How long to wait before the first retry, doubled after every attempt.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+/// How long to wait for the backend to respond when checking that it can be reached.
+const REACHABLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A file to upload.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Media {
+    pub path: PathBuf,
+    pub sha256: String,
+}
+impl Media {
+    pub fn new(path: &Path) -> Result<Self, MainError> {
+        path.canonicalize()
+            .and_then(|path| history::sha256(&path).map(|sha256| Self { path, sha256 }))
+            .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.to_path_buf())))
+    }
+
+    /// Check that the file was not changed since it was queued, returning its path.
+    pub fn check(&self) -> Result<&Path, MainError> {
+        history::sha256(&self.path)
+            .map_err(|error| MainError::ReadFile(error, Cow::Owned(self.path.clone())))
+            .and_then(|sha256| match sha256 == self.sha256 {
+                true => Ok(self.path.as_path()),
+                false => Err(MainError::QueuedFileChanged(Cow::Owned(self.path.clone()))),
+            })
+    }
+}
+
+/// A video to upload, or one that was already uploaded.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Source {
+    File(Media),
+    Url(String),
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Operation {
+    Devlog {
+        message: String,
+        photobooth: Option<Source>,
+        demo: Option<Source>,
+        /// The commit `HEAD` was at when the devlog was written.
+        commit: Option<String>,
+    },
+    Release {
+        /// The body of the request, without the token.
+        payload: Value,
+        /// Screenshots that have not been uploaded yet.
+        screenshots: Vec<Media>,
+        /// Whether the links still have to be checked, as they could not be reached when it was
+        /// queued.
+        #[serde(default)]
+        check_links: bool,
+    },
+}
+impl Operation {
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Devlog { .. } => "devlog",
+            Self::Release { .. } => "release",
+        }
+    }
+
+    pub fn summary(&self) -> &str {
+        match self {
+            Self::Devlog { message, .. } => message,
+            Self::Release { payload, .. } => payload["changesMade"].as_str().unwrap_or_default(),
+        }
+        .lines()
+        .next()
+        .unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Pending {
+    pub time: DateTime<Utc>,
+    pub project: String,
+    pub operation: Operation,
+}
+
+/// Send an operation of `project`, keeping track of its progress in the operation.
+pub type Replay = fn(&str, &mut Operation, &Config) -> Result<(), MainError>;
+
+fn dir() -> Result<&'static Path, MainError> {
+    OUTBOX.as_deref().ok_or(MainError::GetCache)
+}
+
+/// Write `pending` over the file at `path`, or to a new file at the end of the queue.
+fn write(path: Option<&Path>, pending: &Pending, config: &Config) -> Result<(), MainError> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let dir = dir()?;
+            // operations queued within the same millisecond still keep their order
+            (pending.time.timestamp_millis()..)
+                .map(|time| dir.join(format!("{time}.json")))
+                .find(|path| !path.exists())
+                .expect("there should be a free file name")
+        }
+    };
+
+    let contents = serde_json::to_string_pretty(pending).expect("operations should serialize");
+    // queued releases hold the profile, so they are encrypted like it
+    crypt::write(Cow::Owned(path), &contents, config)
+}
+
+/// The files of the queued operations, from oldest to newest.
+fn paths() -> Result<Vec<PathBuf>, MainError> {
+    let dir = dir()?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = fs::read_dir(dir)
+        .map_err(|error| MainError::ReadDirectory(error, Cow::Borrowed(dir)))?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<i64>().ok())
+                .map(|time| (time, path))
+        })
+        .collect::<Vec<_>>();
+    paths.sort_unstable_by_key(|(time, _)| *time);
+
+    Ok(paths.into_iter().map(|(_, path)| path).collect())
+}
+
+/// How many operations are queued, without decrypting them.
+pub fn len() -> Result<usize, MainError> {
+    paths().map(|paths| paths.len())
+}
+
+/// Read every queued operation alongside its path, from oldest to newest.
+pub fn read(config: &Config) -> Result<Vec<(PathBuf, Pending)>, MainError> {
+    paths()?
+        .into_iter()
+        .map(|path| {
+            crypt::read(&path, config).and_then(|pending| {
+                serde_json::from_str(&pending)
+                    .map_err(|error| MainError::ParseQueued(error, Cow::Owned(path.clone())))
+                    .map(|pending| (path, pending))
+            })
+        })
+        .collect()
+}
+
+pub fn remove(path: &Path) -> Result<(), MainError> {
+    fs::remove_file(path)
+        .map_err(|error| MainError::RemoveFile(error, Cow::Owned(path.to_path_buf())))
+}
+
+/// The delay before each retry.
+fn delays() -> impl Iterator<Item = Duration> {
+    (0..ATTEMPTS - 1).map(|retry| RETRY_DELAY * 2_u32.pow(retry))
+}
+
+/// Send `operation`, retrying with a growing delay while the network is down.
+fn attempt(
+    project: &str,
+    operation: &mut Operation,
+    config: &Config,
+    replay: Replay,
+) -> Result<(), MainError> {
+    let mut delays = delays();
+    loop {
+        match replay(project, operation, config) {
+            Err(error) if api::is_offline(&error) => match delays.next() {
+                Some(delay) => {
+                    log::warn!("{error}, retrying in {delay:?}");
+                    thread::sleep(delay);
+                }
+                None => return Err(error),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Replay the queued operations in order, stopping at the first one that fails.
+///
+/// Progress made by the failed operation, such as uploaded files, is kept in the queue.
+pub fn flush(config: &Config, replay: Replay) -> Result<usize, MainError> {
+    let queued = read(config)?;
+    let total = queued.len();
+    for (i, (path, mut pending)) in queued.into_iter().enumerate() {
+        eprintln!(
+            "Sending the queued {} of `{}` from {} ({}/{total})",
+            pending.operation.kind(),
+            pending.project,
+            pending.time.format("%Y-%m-%d %H:%M:%S UTC"),
+            i + 1,
+        );
+        if let Err(error) = attempt(&pending.project, &mut pending.operation, config, replay) {
+            if let Err(error) = write(Some(&path), &pending, config) {
+                log::warn!("failed to save the progress of the queued operation: {error}");
+            }
+            return Err(error);
+        }
+        remove(&path)?;
+    }
+
+    Ok(total)
+}
+
+/// Check that the backend can be reached before the queue is flushed, without retrying, so that a
+/// new operation is not held up when the network is down.
+fn reachable(config: &Config) -> Result<(), MainError> {
+    let client = Client::builder()
+        .timeout(REACHABLE_TIMEOUT)
+        .build()
+        .map_err(MainError::CreateClient)?;
+    runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()
+        .map_err(MainError::CreateRuntime)?
+        .block_on(api::reachable(&client, config))
+}
+
+/// Send `operation`, or queue it if the network is down.
+///
+/// If the backend can be reached, the queued operations are sent first. One that fails for another
+/// reason stays queued with a warning, and `operation` is still sent.
+pub fn send(
+    project: &str,
+    mut operation: Operation,
+    config: &Config,
+    replay: Replay,
+) -> Result<(), MainError> {
+    let result = match len()? {
+        0 => attempt(project, &mut operation, config, replay),
+        _ => reachable(config).and_then(|_| {
+            eprintln!("Sending the queued updates first");
+            match flush(config, replay) {
+                Err(error) if api::is_offline(&error) => return Err(error),
+                Err(error) => log::warn!("{}", MainError::QueueStuck(Box::new(error))),
+                Ok(_) => {}
+            }
+            attempt(project, &mut operation, config, replay)
+        }),
+    };
+
+    match result {
+        Err(error) if api::is_offline(&error) => {
+            let kind = operation.kind();
+            write(
+                None,
+                &Pending {
+                    time: Utc::now(),
+                    project: project.to_string(),
+                    operation,
+                },
+                config,
+            )?;
+            eprintln!(
+                "{error}, the {kind} was queued, send it with `neighborhood_cli queue flush`"
+            );
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_policy() {
+        assert_eq!(
+            delays().collect::<Vec<_>>(),
+            [Duration::from_secs(2), Duration::from_secs(4)]
+        );
+    }
+
+    #[test]
+    fn sources() {
+        let devlog = serde_json::json!({
+            "kind": "devlog",
+            "message": "queued before urls were supported",
+            "photobooth": { "path": "/photobooth.mp4", "sha256": "abc" },
+            "demo": { "path": "/demo.mp4", "sha256": "def" },
+        });
+        assert!(matches!(
+            serde_json::from_value(devlog).unwrap(),
+            Operation::Devlog {
+                photobooth: Some(Source::File(_)),
+                demo: Some(Source::File(_)),
+                ..
+            }
+        ));
+
+        let devlog = serde_json::json!({
+            "kind": "devlog",
+            "message": "reusing a demo",
+            "demo": "https://example.com/demo.mp4",
+        });
+        assert!(matches!(
+            serde_json::from_value(devlog).unwrap(),
+            Operation::Devlog {
+                photobooth: None,
+                demo: Some(Source::Url(url)),
+                ..
+            } if url == "https://example.com/demo.mp4"
+        ));
+    }
+
+    #[test]
+    fn changed_media() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("demo.mp4");
+        fs::write(&path, "abc").unwrap();
+
+        let media = Media::new(&path).unwrap();
+        assert!(media.path.is_absolute());
+        assert_eq!(media.check().unwrap(), media.path);
+
+        fs::write(&path, "abcd").unwrap();
+        assert!(matches!(
+            media.check(),
+            Err(MainError::QueuedFileChanged(_))
+        ));
+        assert!(Media::new(&dir.path().join("missing.mp4")).is_err());
+    }
+}
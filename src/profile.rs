@@ -0,0 +1,250 @@
+//! Personal information shared between the release forms of every project
+//!
+//! Any key in here can be overridden by adding it to the release form of a project.
+
+use {
+    crate::{
+        MainError, autofill,
+        cache::PROFILE,
+        config::Config,
+        crypt,
+        editor::Editor,
+        form::{
+            check, check_zip_code, deserialize_some_birthday, deserialize_some_country,
+            deserialize_some_email, deserialize_some_non_empty_string, serialize_some_iso_8601,
+        },
+        schema,
+    },
+    schemars::JsonSchema,
+    serde::{Deserialize, Serialize},
+    std::borrow::Cow,
+    toml_edit::{Date, DocumentMut, TomlError},
+};
+
+/// Every key of [Profile].
+pub const FIELDS: &[&str] = &[
+    "addressLine1",
+    "addressLine2",
+    "birthday",
+    "city",
+    "country",
+    "email",
+    "githubUsername",
+    "firstName",
+    "lastName",
+    "stateProvince",
+    "zipCode",
+];
+
+/// Your personal information, shared between the release forms of all of your projects
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+#[schemars(title = "Neighborhood profile", deny_unknown_fields)]
+pub struct Profile {
+    /// The first line of your address
+    #[serde(
+        deserialize_with = "deserialize_some_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(length(min = 1))]
+    address_line_1: Option<String>,
+    /// The second line of your address, optional
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address_line_2: Option<String>,
+    /// ISO-8601 format (YYYY-MM-DD)
+    #[serde(
+        deserialize_with = "deserialize_some_birthday",
+        serialize_with = "serialize_some_iso_8601",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<String>", extend("format" = "date"))]
+    birthday: Option<Date>,
+    /// The city you live in
+    #[serde(
+        deserialize_with = "deserialize_some_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(length(min = 1))]
+    city: Option<String>,
+    /// ISO 3166-1 alpha-2 country code, such as "US"
+    #[serde(
+        deserialize_with = "deserialize_some_country",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(extend("enum" = check::COUNTRIES))]
+    country: Option<String>,
+    /// The email used to sign up into neighborhood
+    #[serde(
+        deserialize_with = "deserialize_some_email",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(email)]
+    email: Option<String>,
+    /// Your GitHub username
+    #[serde(
+        deserialize_with = "deserialize_some_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(length(min = 1))]
+    github_username: Option<String>,
+    /// Your first name
+    #[serde(
+        deserialize_with = "deserialize_some_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(length(min = 1))]
+    first_name: Option<String>,
+    /// Your last name
+    #[serde(
+        deserialize_with = "deserialize_some_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(length(min = 1))]
+    last_name: Option<String>,
+    /// The state or province you live in
+    #[serde(
+        deserialize_with = "deserialize_some_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(length(min = 1))]
+    state_province: Option<String>,
+    /// Your zip or postal code
+    #[serde(
+        deserialize_with = "deserialize_some_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(length(min = 1))]
+    zip_code: Option<String>,
+}
+impl Profile {
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    /// Use the values of `overrides` where they are set.
+    pub fn merge(self, overrides: Self) -> Self {
+        Self {
+            address_line_1: overrides.address_line_1.or(self.address_line_1),
+            address_line_2: overrides.address_line_2.or(self.address_line_2),
+            birthday: overrides.birthday.or(self.birthday),
+            city: overrides.city.or(self.city),
+            country: overrides.country.or(self.country),
+            email: overrides.email.or(self.email),
+            github_username: overrides.github_username.or(self.github_username),
+            first_name: overrides.first_name.or(self.first_name),
+            last_name: overrides.last_name.or(self.last_name),
+            state_province: overrides.state_province.or(self.state_province),
+            zip_code: overrides.zip_code.or(self.zip_code),
+        }
+    }
+
+    /// Ensure that every required field is set.
+    pub fn complete(mut self) -> Result<Self, MainError> {
+        [
+            ("addressLine1", self.address_line_1.is_some()),
+            ("birthday", self.birthday.is_some()),
+            ("city", self.city.is_some()),
+            ("country", self.country.is_some()),
+            ("email", self.email.is_some()),
+            ("githubUsername", self.github_username.is_some()),
+            ("firstName", self.first_name.is_some()),
+            ("lastName", self.last_name.is_some()),
+            ("stateProvince", self.state_province.is_some()),
+            ("zipCode", self.zip_code.is_some()),
+        ]
+        .into_iter()
+        .find(|(_, set)| !set)
+        .map_or(Ok(()), |(field, _)| {
+            Err(MainError::IncompleteProfile(field))
+        })?;
+        // the country and zip code may come from different files
+        if let (Some(country), Some(zip_code)) = (&self.country, &self.zip_code) {
+            check::zip_code(zip_code, country).map_err(MainError::InvalidZipCode)?;
+        }
+
+        self.address_line_2.get_or_insert_default();
+        Ok(self)
+    }
+}
+
+pub const INITIAL_PROFILE: &str = r#"# Personal information shared between all of your projects
+# Any of these can be overridden for a single project by adding them to its release form
+addressLine1 = ""
+addressLine2 = "" # Optional
+birthday = 2000-01-01 # ISO-8601 format (YYYY-MM-DD)
+city = ""
+country = "" # ISO 3166-1 alpha-2 country code, such as "US"
+email = "" # The email used to sign up into neighborhood
+githubUsername = ""
+firstName = ""
+lastName = ""
+stateProvince = ""
+zipCode = ""
+"#;
+
+pub fn validate(profile: &str) -> Result<DocumentMut, TomlError> {
+    profile.parse::<DocumentMut>().and_then(|document| {
+        toml_edit::de::from_str::<Profile>(profile)
+            .map_err(TomlError::from)
+            .and_then(|parsed| {
+                parsed
+                    .country()
+                    .map_or(Ok(()), |country| check_zip_code(profile, country))
+            })
+            .map(move |_| document)
+    })
+}
+
+/// Read the profile, an empty profile is returned if it does not exist.
+pub fn read(config: &Config) -> Result<Profile, MainError> {
+    let path = PROFILE.as_ref().ok_or(MainError::GetCache)?;
+    if !path.is_file() {
+        return Ok(Profile::default());
+    }
+
+    crypt::read(path, config).and_then(|profile| {
+        toml_edit::de::from_str(&profile)
+            .map_err(TomlError::from)
+            .map_err(MainError::ParseProfile)
+    })
+}
+
+pub fn exists() -> bool {
+    PROFILE.as_ref().is_some_and(|path| path.is_file())
+}
+
+/// Edit the profile, returning whether it was saved.
+pub fn edit(
+    editor: &Editor,
+    config: &Config,
+    no_confirm: bool,
+    reset: bool,
+) -> Result<bool, MainError> {
+    let path = PROFILE.as_ref().ok_or(MainError::GetCache)?;
+    let contents = if !path.is_file() || reset {
+        let mut document = INITIAL_PROFILE
+            .parse::<DocumentMut>()
+            .expect("the initial profile should be valid toml");
+        autofill::profile(&mut document);
+        Cow::Owned(
+            schema::directive("profile", &schema::profile()).unwrap_or_default()
+                + &document.to_string(),
+        )
+    } else {
+        crypt::read(path, config).map(Cow::Owned)?
+    };
+
+    match editor.edit(
+        "profile.toml",
+        &contents,
+        None,
+        (!no_confirm).then_some("Save profile:"),
+        validate,
+    )? {
+        Some(document) => {
+            crypt::write(Cow::Borrowed(path), &document.to_string(), config)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
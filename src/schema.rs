@@ -0,0 +1,81 @@
+//! JSON schemas of the forms, which editors pick up through the `#:schema` directive of taplo
+//!
+//! Schemas are generated from the types that the forms are read into, whose doc comments are the
+//! descriptions of the fields.
+
+use {
+    crate::{
+        cache::{SCHEMAS, write_file},
+        profile::{self, Profile},
+    },
+    reqwest::Url,
+    schemars::{JsonSchema, Schema, generate::SchemaSettings, transform::RecursiveTransform},
+    serde_json::Value,
+    std::borrow::Cow,
+};
+
+/// Remove `null` from the types of optional fields, which are left out instead as toml has no null.
+fn remove_null(schema: &mut Schema) {
+    if let Some(Value::Array(types)) = schema.get_mut("type") {
+        types.retain(|ty| ty != "null");
+        if let [ty] = types.as_slice() {
+            let ty = ty.clone();
+            schema.insert("type".to_string(), ty);
+        }
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get_mut("anyOf") {
+        schemas.retain(|schema| schema["type"] != "null");
+        if let [Value::Object(only)] = schemas.as_slice() {
+            let only = only.clone();
+            schema.remove("anyOf");
+            only.into_iter().for_each(|(key, value)| {
+                if schema.get(&key).is_none() {
+                    schema.insert(key, value);
+                }
+            });
+        }
+    }
+}
+
+/// Generate the schema of a form read into `T`.
+pub fn generate<T>() -> Value
+where
+    T: JsonSchema,
+{
+    SchemaSettings::draft07()
+        .with_transform(RecursiveTransform(remove_null))
+        .into_generator()
+        .into_root_schema_for::<T>()
+        .to_value()
+}
+
+pub fn profile() -> Value {
+    let mut schema = generate::<Profile>();
+    // the fields can be left out of release forms, which only override the profile
+    schema["required"] = profile::FIELDS
+        .iter()
+        .filter(|field| **field != "addressLine2")
+        .copied()
+        .collect();
+    schema
+}
+
+/// Write `schema` to the cache, returning a `#:schema` directive pointing to it.
+///
+/// Failing to write the schema only loses editor support, so `None` is returned instead of an error.
+pub fn directive(name: &str, schema: &Value) -> Option<String> {
+    let mut path = SCHEMAS.clone()?;
+    path.push(name);
+    path.set_extension("json");
+
+    write_file(
+        Cow::Owned(path.clone()),
+        serde_json::to_string_pretty(schema).ok()?.as_bytes(),
+    )
+    .inspect_err(|error| log::debug!("failed to write schema: {error}"))
+    .ok()?;
+    Url::from_file_path(&path)
+        .ok()
+        .map(|url| format!("#:schema {url}\n"))
+}
@@ -1,14 +1,33 @@
 mod auth;
+mod completions;
+mod config;
+mod docs;
+mod encrypt;
+mod profile;
 mod project;
+mod queue;
+mod schema;
 
-use {crate::MainError, clap::ArgMatches};
+use {
+    crate::{MainError, config::Config},
+    clap::ArgMatches,
+};
 
+/// Run a subcommand, loading the config only for the ones that need it so that a broken config
+/// can still be fixed with `config`.
 pub fn execute(mut args: ArgMatches) -> Result<(), MainError> {
     let (subcommand, args) = args.remove_subcommand().unwrap();
 
     match subcommand.as_str() {
-        "auth" => auth::execute(args),
-        "project" => project::execute(args),
+        "auth" => auth::execute(args, &Config::load()?),
+        "completions" => completions::execute(args),
+        "config" => config::execute(args, &Config::load_lenient()),
+        "docs" => docs::execute(args),
+        "encrypt" => encrypt::execute(&Config::load()?),
+        "profile" => profile::execute(args, &Config::load()?),
+        "project" => project::execute(args, &Config::load()?),
+        "queue" => queue::execute(args, &Config::load()?),
+        "schema" => schema::execute(args),
         _ => unreachable!(),
     }
 }
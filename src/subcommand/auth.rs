@@ -1,16 +1,17 @@
 use {
     crate::{
         MainError,
-        api::MessageResponse,
+        api::{self, MessageResponse},
         cache::{TOKEN, write_file},
+        config::Config,
     },
     clap::ArgMatches,
-    reqwest::blocking::{Client, Response},
+    reqwest::blocking::Client,
     serde::{Deserialize, Serialize},
     std::{borrow::Cow, fs::DirBuilder},
 };
 
-pub fn execute(mut args: ArgMatches) -> Result<(), MainError> {
+pub fn execute(mut args: ArgMatches, config: &Config) -> Result<(), MainError> {
     Client::builder()
         .build()
         .map_err(MainError::CreateClient)
@@ -22,24 +23,14 @@ pub fn execute(mut args: ArgMatches) -> Result<(), MainError> {
                 }
 
                 let email = args.remove_one::<String>("email").unwrap();
-                client
-                    .post("https://neighborhood.hackclub.com/api/signup")
-                    .json(&Signup { email: &email })
-                    .send()
-                    .and_then(Response::error_for_status)
-                    .map_err(MainError::ExecuteRequest)
-                    .and_then(|response| {
-                        response
-                            .text()
-                            .map_err(MainError::ExecuteRequest)
-                            .and_then(|response| {
-                                serde_json::from_str(&response)
-                                    .map_err(|error| MainError::DecodeResponse(error, response))
-                            })
-                            .map(|MessageResponse { message }| {
-                                eprintln!("{message}");
-                            })
-                    })
+                api::send_blocking(
+                    client
+                        .post(format!("{}/api/signup", config.api()))
+                        .json(&Signup { email: &email }),
+                )
+                .map(|MessageResponse { message }| {
+                    eprintln!("{message}");
+                })
             }
             ("login", login_args) => {
                 #[derive(Serialize)]
@@ -55,40 +46,28 @@ pub fn execute(mut args: ArgMatches) -> Result<(), MainError> {
 
                 let email = args.get_one::<String>("email").unwrap();
                 let otp = login_args.get_one::<String>("otp").unwrap();
-                client
-                    .post("https://neighborhood.hackclub.com/api/verifyOTP")
-                    .json(&VerifyOtp { email, otp })
-                    .send()
-                    .and_then(Response::error_for_status)
-                    .map_err(MainError::ExecuteRequest)
-                    .and_then(|response| {
-                        response
-                            .text()
-                            .map_err(MainError::ExecuteRequest)
-                            .and_then(|response| {
-                                serde_json::from_str(&response)
-                                    .map_err(|error| MainError::DecodeResponse(error, response))
-                            })
-                            .inspect(|VerifyOtpResponse { message, .. }| eprintln!("{message}"))
-                            .and_then(|VerifyOtpResponse { token, .. }| {
-                                let path = TOKEN.as_ref().ok_or(MainError::GetCache)?;
+                api::send_blocking(
+                    client
+                        .post(format!("{}/api/verifyOTP", config.api()))
+                        .json(&VerifyOtp { email, otp }),
+                )
+                .inspect(|VerifyOtpResponse { message, .. }| eprintln!("{message}"))
+                .and_then(|VerifyOtpResponse { token, .. }| {
+                    let path = TOKEN.as_ref().ok_or(MainError::GetCache)?;
 
-                                if let Some(parent) = path.parent() {
-                                    if !parent.is_dir() {
-                                        DirBuilder::new().recursive(true).create(parent).map_err(
-                                            |error| {
-                                                MainError::CreateDirectory(
-                                                    error,
-                                                    Cow::Borrowed(path),
-                                                )
-                                            },
-                                        )?;
-                                    }
-                                }
+                    if let Some(parent) = path.parent() {
+                        if !parent.is_dir() {
+                            DirBuilder::new()
+                                .recursive(true)
+                                .create(parent)
+                                .map_err(|error| {
+                                    MainError::CreateDirectory(error, Cow::Borrowed(path))
+                                })?;
+                        }
+                    }
 
-                                write_file(Cow::Borrowed(path), token.as_bytes())
-                            })
-                    })
+                    write_file(Cow::Borrowed(path), token.as_bytes())
+                })
             }
             _ => unreachable!(),
         })
@@ -0,0 +1,23 @@
+use {
+    crate::MainError,
+    clap::ArgMatches,
+    clap_complete::env::Shells,
+    std::io::{self, Write},
+};
+
+/// The environment variable that [clap_complete::CompleteEnv] checks for in `main`.
+const VAR: &str = "COMPLETE";
+const SHELLS: Shells<'static> = Shells::builtins();
+
+pub fn execute(args: ArgMatches) -> Result<(), MainError> {
+    let shell = args.get_one::<String>("shell").unwrap();
+    let shell = SHELLS
+        .completer(shell)
+        .expect("`shell` should only accept shells supported by `clap_complete`");
+
+    let mut stdout = io::stdout().lock();
+    shell
+        .write_registration(VAR, crate::NAME, crate::NAME, crate::NAME, &mut stdout)
+        .and_then(|_| stdout.flush())
+        .map_err(MainError::WriteStdout)
+}
@@ -0,0 +1,51 @@
+use {
+    crate::{
+        MainError,
+        config::{self, Config, KEYS, REPO_FILE_NAME, USER},
+    },
+    clap::ArgMatches,
+    std::path::PathBuf,
+};
+
+pub fn execute(mut args: ArgMatches, config: &Config) -> Result<(), MainError> {
+    let (subcommand, mut args) = args.remove_subcommand().unwrap();
+
+    match subcommand.as_str() {
+        "get" => {
+            let key = args.remove_one::<String>("key").unwrap();
+
+            config
+                .get(&key)
+                .map(|(value, _)| match value.as_str() {
+                    Some(value) => println!("{value}"),
+                    None => println!("{}", value.to_string().trim()),
+                })
+                .ok_or(MainError::UnsetConfigKey(key))
+        }
+        "set" => {
+            let key = args.remove_one::<String>("key").unwrap();
+            let value = args.remove_one::<String>("value").unwrap();
+
+            let repo = args.get_flag("repo");
+            let path = if repo {
+                config::find_repo().unwrap_or_else(|| PathBuf::from(REPO_FILE_NAME))
+            } else {
+                USER.clone().ok_or(MainError::GetConfig)?
+            };
+            config::set(&path, &key, &value, repo)
+        }
+        "list" => {
+            KEYS.iter().for_each(|(key, description)| {
+                println!("# {description}");
+                match config.get(key) {
+                    Some((value, source)) => {
+                        println!("{key} = {} # from {source}", value.to_string().trim())
+                    }
+                    None => println!("# {key} ="),
+                }
+            });
+            Ok(())
+        }
+        _ => unreachable!(),
+    }
+}
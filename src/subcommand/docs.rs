@@ -0,0 +1,100 @@
+use {
+    crate::{MainError, cache::create_if_not_dir},
+    clap::{ArgMatches, Command},
+    std::{
+        borrow::Cow,
+        fmt::{self, Write},
+        fs,
+        path::PathBuf,
+    },
+};
+
+fn write_markdown(command: &Command, out: &mut String) -> Result<(), fmt::Error> {
+    let name = command
+        .get_bin_name()
+        .unwrap_or(command.get_name())
+        .to_string();
+    let depth = name.split(' ').count().min(6);
+
+    writeln!(out, "{} `{name}`\n", "#".repeat(depth))?;
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        writeln!(out, "{about}\n")?;
+    }
+    writeln!(out, "```\n{}\n```\n", command.clone().render_usage())?;
+
+    let mut positionals = command
+        .get_positionals()
+        .filter(|arg| !arg.is_hide_set())
+        .peekable();
+    if positionals.peek().is_some() {
+        writeln!(out, "**Arguments:**\n")?;
+        for arg in positionals {
+            write!(out, "- `<{}>`", arg.get_id())?;
+            if let Some(help) = arg.get_help() {
+                write!(out, ": {help}")?;
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    let mut options = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+        .peekable();
+    if options.peek().is_some() {
+        writeln!(out, "**Options:**\n")?;
+        for arg in options {
+            out.push_str("- ");
+            if let Some(short) = arg.get_short() {
+                write!(out, "`-{short}`, ")?;
+            }
+            if let Some(long) = arg.get_long() {
+                write!(out, "`--{long}`")?;
+            }
+            if arg.get_action().takes_values() {
+                match arg.get_value_names() {
+                    Some(names) => names
+                        .iter()
+                        .try_for_each(|value_name| write!(out, " `<{value_name}>`"))?,
+                    None => write!(out, " `<{}>`", arg.get_id())?,
+                }
+            }
+            if let Some(help) = arg.get_help() {
+                write!(out, ": {help}")?;
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .try_for_each(|subcommand| write_markdown(subcommand, out))
+}
+
+pub fn execute(mut args: ArgMatches) -> Result<(), MainError> {
+    let format = args.remove_one::<String>("format").unwrap();
+    let out_dir = args.remove_one::<PathBuf>("out-dir").unwrap();
+    create_if_not_dir(Cow::Owned(out_dir.clone()))?;
+
+    let mut command = crate::root_command().disable_help_subcommand(true);
+    command.build();
+
+    match format.as_str() {
+        "man" => clap_mangen::generate_to(command, &out_dir)
+            .map_err(|error| MainError::WriteFile(error, Cow::Owned(out_dir))),
+        "markdown" => {
+            let mut markdown = String::new();
+            write_markdown(&command, &mut markdown).expect("writing to a `String` should not fail");
+
+            let mut path = out_dir;
+            path.push(crate::NAME);
+            path.set_extension("md");
+            fs::write(&path, markdown)
+                .map_err(|error| MainError::WriteFile(error, Cow::Owned(path)))
+        }
+        _ => unreachable!(),
+    }
+}
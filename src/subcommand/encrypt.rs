@@ -0,0 +1,13 @@
+//! Encrypting the files cached before encryption was enabled
+
+use crate::{MainError, config::Config, crypt};
+
+pub fn execute(config: &Config) -> Result<(), MainError> {
+    match crypt::encrypt_existing(config)?.as_slice() {
+        [] => eprintln!("Every cached file is already encrypted"),
+        paths => paths
+            .iter()
+            .for_each(|path| eprintln!("Encrypted `{}`", path.display())),
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use {
+    crate::{MainError, config::Config, editor::Editor, profile},
+    clap::ArgMatches,
+};
+
+pub fn execute(mut args: ArgMatches, config: &Config) -> Result<(), MainError> {
+    let (subcommand, mut args) = args.remove_subcommand().unwrap();
+
+    match subcommand.as_str() {
+        "edit" => {
+            let no_confirm = args.get_flag("no-confirm") || config.no_confirm();
+            let reset = args.get_flag("reset");
+
+            profile::edit(
+                &Editor::from_args(&mut args, config)?,
+                config,
+                no_confirm,
+                reset,
+            )
+            .map(|_| ())
+        }
+        _ => unreachable!(),
+    }
+}
@@ -1,13 +1,22 @@
-mod update;
+mod devlogs;
+mod releases;
+mod screenshots;
+pub mod update;
 
-use {crate::MainError, clap::ArgMatches};
+use {
+    crate::{MainError, config::Config},
+    clap::ArgMatches,
+};
 
-pub fn execute(mut args: ArgMatches) -> Result<(), MainError> {
+pub fn execute(mut args: ArgMatches, config: &Config) -> Result<(), MainError> {
     let name = args.remove_one::<String>("name").unwrap();
 
     let (subcommand, args) = args.remove_subcommand().unwrap();
     match subcommand.as_str() {
-        "update" => update::execute(args, &name),
+        "devlogs" => devlogs::execute(args, config, &name),
+        "releases" => releases::execute(args, config, &name),
+        "screenshots" => screenshots::execute(args, config, &name),
+        "update" => update::execute(args, config, &name),
         _ => unreachable!(),
     }
 }
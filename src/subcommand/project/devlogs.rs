@@ -0,0 +1,70 @@
+//! Looking back at the devlogs that have been posted
+
+use {
+    crate::{
+        MainError,
+        config::Config,
+        history::{self, Devlog, Video},
+    },
+    clap::ArgMatches,
+};
+
+fn get(devlogs: &[Devlog], n: usize) -> Result<&Devlog, MainError> {
+    history::nth(devlogs, n).ok_or(MainError::UnknownDevlog(n))
+}
+
+fn summary(devlog: &Devlog) -> &str {
+    devlog.message.lines().next().unwrap_or_default()
+}
+
+fn print_video(name: &str, video: Option<&Video>) {
+    let Some(video) = video else {
+        return println!("{name}: none");
+    };
+    println!("{name}: {}", video.url);
+    if let Some(path) = &video.path {
+        println!("  path: {}", path.display());
+    }
+    if let Some(sha256) = &video.sha256 {
+        println!("  sha256: {sha256}");
+    }
+}
+
+pub fn execute(mut args: ArgMatches, config: &Config, name: &str) -> Result<(), MainError> {
+    let devlogs = history::devlogs(name, config)?;
+
+    let (subcommand, mut args) = args.remove_subcommand().unwrap();
+    let json = args.get_flag("json");
+    match subcommand.as_str() {
+        "list" if json => println!(
+            "{}",
+            serde_json::to_string_pretty(&devlogs).unwrap_or_default()
+        ),
+        "list" => devlogs.iter().enumerate().for_each(|(i, devlog)| {
+            println!(
+                "{}: {} {}",
+                i + 1,
+                devlog.time.format("%Y-%m-%d %H:%M:%S UTC"),
+                summary(devlog)
+            )
+        }),
+        "show" => {
+            let devlog = get(&devlogs, args.remove_one::<usize>("n").unwrap())?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(devlog).unwrap_or_default()
+                );
+            } else {
+                println!("time: {}", devlog.time.to_rfc3339());
+                println!("response: {}", devlog.response);
+                print_video("demo", devlog.demo.as_ref());
+                print_video("photobooth", devlog.photobooth.as_ref());
+                println!("\n{}", devlog.message);
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
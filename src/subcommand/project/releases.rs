@@ -0,0 +1,123 @@
+//! Looking back at the releases that have been shipped
+
+use {
+    crate::{
+        MainError,
+        config::Config,
+        history::{self, Release},
+    },
+    clap::ArgMatches,
+    serde_json::Value,
+    std::collections::BTreeSet,
+};
+
+fn get(releases: &[Release], n: usize) -> Result<&Release, MainError> {
+    history::nth(releases, n).ok_or(MainError::UnknownRelease(n))
+}
+
+fn summary(release: &Release) -> &str {
+    release
+        .payload
+        .get("changesMade")
+        .and_then(Value::as_str)
+        .and_then(|message| message.lines().next())
+        .unwrap_or_default()
+}
+
+/// The changed fields between two payloads, as lines prefixed with `-` or `+`.
+fn diff(old: &Value, new: &Value) -> Vec<String> {
+    let keys = [old, new]
+        .into_iter()
+        .filter_map(Value::as_object)
+        .flat_map(|object| object.keys())
+        .collect::<BTreeSet<_>>();
+
+    keys.into_iter()
+        .flat_map(|key| match (old.get(key), new.get(key)) {
+            (old, new) if old == new => Vec::new(),
+            (Some(Value::Array(old)), Some(Value::Array(new))) => old
+                .iter()
+                .filter(|value| !new.contains(value))
+                .map(|value| format!("-{key}[]: {value}"))
+                .chain(
+                    new.iter()
+                        .filter(|value| !old.contains(value))
+                        .map(|value| format!("+{key}[]: {value}")),
+                )
+                .collect(),
+            (old, new) => old
+                .map(|old| format!("-{key}: {old}"))
+                .into_iter()
+                .chain(new.map(|new| format!("+{key}: {new}")))
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn execute(mut args: ArgMatches, config: &Config, name: &str) -> Result<(), MainError> {
+    let releases = history::releases(name, config)?;
+
+    let (subcommand, mut args) = args.remove_subcommand().unwrap();
+    match subcommand.as_str() {
+        "list" => releases.iter().enumerate().for_each(|(i, release)| {
+            println!(
+                "{}: {} {}",
+                i + 1,
+                release.time.format("%Y-%m-%d %H:%M:%S UTC"),
+                summary(release)
+            )
+        }),
+        "show" => {
+            let release = get(&releases, args.remove_one::<usize>("n").unwrap())?;
+            println!("time: {}", release.time.to_rfc3339());
+            println!("response: {}", release.response);
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&release.payload).unwrap_or_default()
+            );
+        }
+        "diff" => {
+            let old = get(&releases, args.remove_one::<usize>("a").unwrap())?;
+            let new = get(&releases, args.remove_one::<usize>("b").unwrap())?;
+            diff(&old.payload, &new.payload)
+                .into_iter()
+                .for_each(|line| println!("{line}"));
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn payload_diff() {
+        assert_eq!(
+            diff(
+                &json!({
+                    "changesMade": "first",
+                    "description": "same",
+                    "screenshots": ["a", "b"],
+                    "email": "<redacted>",
+                }),
+                &json!({
+                    "changesMade": "second",
+                    "description": "same",
+                    "screenshots": ["b", "c"],
+                    "playableUrl": "https://example.com",
+                }),
+            ),
+            [
+                r#"-changesMade: "first""#,
+                r#"+changesMade: "second""#,
+                r#"-email: "<redacted>""#,
+                r#"+playableUrl: "https://example.com""#,
+                r#"-screenshots[]: "a""#,
+                r#"+screenshots[]: "c""#,
+            ]
+        );
+    }
+}
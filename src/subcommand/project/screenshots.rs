@@ -0,0 +1,203 @@
+//! Editing the screenshots of the cached release form without an editor
+
+use {
+    super::update::{UploadApi, UploadImages},
+    crate::{
+        MainError,
+        cache::{read_token, release_form},
+        config::{Config, PROJECT_DIR},
+        crypt,
+        form::check,
+    },
+    clap::ArgMatches,
+    reqwest::Client,
+    std::{borrow::Cow, collections::HashSet},
+    tokio::runtime,
+    toml_edit::{Array, DocumentMut, Item, Value},
+};
+
+/// Put the values at the indices in `order` into the first positions of `array`, dropping the rest.
+///
+/// The formatting of every position is kept, so that moving a value does not move its whitespace
+/// or comments with it.
+fn arrange(array: &mut Array, order: &[usize]) {
+    let values = array.iter().cloned().collect::<Vec<_>>();
+
+    order.iter().enumerate().for_each(|(position, &i)| {
+        if let Some(slot) = array.get_mut(position) {
+            let decor = slot.decor().clone();
+            *slot = values[i].clone();
+            *slot.decor_mut() = decor;
+        }
+    });
+    (order.len()..values.len()).rev().for_each(|position| {
+        array.remove(position);
+    });
+}
+
+/// Find the index of a screenshot from either its number, starting from 1, or its url.
+fn find(array: &Array, screenshot: &str) -> Result<usize, MainError> {
+    screenshot
+        .parse::<usize>()
+        .ok()
+        .filter(|&n| 0 < n && n <= array.len())
+        .map(|n| n - 1)
+        .or_else(|| {
+            array
+                .iter()
+                .position(|url| url.as_str() == Some(screenshot))
+        })
+        .ok_or_else(|| MainError::UnknownScreenshot(screenshot.to_string()))
+}
+
+fn screenshots(document: &mut DocumentMut) -> Result<&mut Array, MainError> {
+    document
+        .entry("screenshots")
+        .or_insert_with(|| Item::Value(Value::Array(Array::new())))
+        .as_array_mut()
+        .ok_or(MainError::ScreenshotsNotArray)
+}
+
+pub fn execute(mut args: ArgMatches, config: &Config, name: &str) -> Result<(), MainError> {
+    let path = release_form(name)?;
+    if !path.is_file() {
+        return Err(MainError::NoReleaseForm(name.to_string()));
+    }
+    let mut document = crypt::read(&path, config)?
+        .parse::<DocumentMut>()
+        .map_err(MainError::ParseReleaseConfig)?;
+
+    let (subcommand, mut args) = args.remove_subcommand().unwrap();
+    match subcommand.as_str() {
+        "list" => {
+            screenshots(&mut document)?
+                .iter()
+                .enumerate()
+                .for_each(|(i, url)| match url.as_str() {
+                    Some(url) => println!("{}: {url}", i + 1),
+                    None => println!("{}: {}", i + 1, url.to_string().trim()),
+                });
+            document
+                .get("newScreenshotPaths")
+                .and_then(Item::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .for_each(|path| println!("+: {path} (uploaded on the next release)"));
+            return Ok(());
+        }
+        "add" => {
+            let (urls, paths) = args
+                .remove_many::<String>("screenshots")
+                .unwrap()
+                .partition::<Vec<_>, _>(|screenshot| check::url(screenshot).is_ok());
+            // paths are relative to the root of the project, and checked before anything is uploaded
+            let paths = paths
+                .into_iter()
+                .map(|path| {
+                    let path = PROJECT_DIR.join(path);
+                    check::image(&path)
+                        .map(|_| path)
+                        .map_err(MainError::InvalidScreenshot)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let uploaded = if paths.is_empty() {
+                Vec::new()
+            } else {
+                let token = read_token()?;
+                let client = Client::builder().build().map_err(MainError::CreateClient)?;
+                runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .enable_time()
+                    .build()
+                    .map_err(MainError::CreateRuntime)?
+                    .block_on(UploadImages::new(paths).upload(&client, config, token))?
+            };
+
+            let screenshots = screenshots(&mut document)?;
+            urls.into_iter()
+                .chain(uploaded.into_iter().filter(|url| !url.is_empty()))
+                .for_each(|url| {
+                    println!("{}: {url}", screenshots.len() + 1);
+                    screenshots.push(url);
+                });
+        }
+        "remove" => {
+            let screenshots = screenshots(&mut document)?;
+            let removed = args
+                .remove_many::<String>("screenshots")
+                .unwrap()
+                .map(|screenshot| find(screenshots, &screenshot))
+                .collect::<Result<HashSet<_>, _>>()?;
+
+            let order = (0..screenshots.len())
+                .filter(|i| !removed.contains(i))
+                .collect::<Vec<_>>();
+            arrange(screenshots, &order);
+        }
+        "reorder" => {
+            let screenshots = screenshots(&mut document)?;
+            let listed = args
+                .remove_many::<String>("order")
+                .unwrap()
+                .map(|screenshot| find(screenshots, &screenshot))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // anything left out keeps its order after the listed screenshots
+            let mut seen = HashSet::with_capacity(screenshots.len());
+            let order = listed
+                .into_iter()
+                .chain(0..screenshots.len())
+                .filter(|&i| seen.insert(i))
+                .collect::<Vec<_>>();
+            arrange(screenshots, &order);
+        }
+        _ => unreachable!(),
+    }
+
+    crypt::write(Cow::Owned(path), &document.to_string(), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrange_keeps_formatting() {
+        let mut document = r#"screenshots = [
+    "a", # first
+    "b",
+    "c",
+]
+"#
+        .parse::<DocumentMut>()
+        .unwrap();
+        let array = screenshots(&mut document).unwrap();
+        assert_eq!(find(array, "c").unwrap(), 2);
+        assert_eq!(find(array, "1").unwrap(), 0);
+        assert_eq!(find(array, "3").unwrap(), 2);
+        assert!(find(array, "0").is_err());
+        assert!(find(array, "4").is_err());
+
+        arrange(array, &[2, 0, 1]);
+        assert_eq!(
+            document.to_string(),
+            r#"screenshots = [
+    "c", # first
+    "a",
+    "b",
+]
+"#
+        );
+
+        arrange(screenshots(&mut document).unwrap(), &[1]);
+        assert_eq!(
+            document.to_string(),
+            r#"screenshots = [
+    "a",
+]
+"#
+        );
+    }
+}
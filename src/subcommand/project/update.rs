@@ -1,40 +1,37 @@
 mod devlog;
-mod release;
+mod message;
+pub mod release;
 
 use {
-    crate::MainError,
+    crate::{MainError, api, config::Config, outbox::Operation},
     clap::ArgMatches,
     futures_lite::stream::{self, StreamExt},
     reqwest::{
-        Client, Response,
+        Client,
         multipart::{Form, Part},
     },
     serde::{Deserialize, de::DeserializeOwned},
-    std::{
-        borrow::Cow,
-        path::{self, PathBuf},
-        pin::pin,
-    },
+    std::{borrow::Cow, path::Path, pin::pin},
     tokio::fs,
 };
 
-async fn path_to_part<S>(path: S) -> Result<Part, MainError>
+async fn path_to_part<P>(path: P) -> Result<Part, MainError>
 where
-    S: AsRef<str>,
+    P: AsRef<Path>,
 {
+    let path = path.as_ref();
     let file = Part::bytes(
-        fs::read(path.as_ref())
+        fs::read(path)
             .await
-            .map_err(|err| MainError::ReadFile(err, Cow::Owned(PathBuf::from(path.as_ref()))))?,
+            .map_err(|err| MainError::ReadFile(err, Cow::Owned(path.to_path_buf())))?,
     )
     .file_name(
-        path.as_ref()
-            .rsplit_once(path::MAIN_SEPARATOR)
-            .map(|(_, r)| r)
-            .unwrap_or(path.as_ref())
-            .to_string(),
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned(),
     );
-    let file = if let Some(mime) = mime_guess::from_path(path.as_ref()).first() {
+    let file = if let Some(mime) = mime_guess::from_path(path).first() {
         file.mime_str(mime.essence_str())
             .expect("the `mime_guess` crate should be outputting valid mime strings")
     } else {
@@ -45,6 +42,7 @@ where
 }
 
 pub trait UploadApi: Sized {
+    /// The path of this api relative to the upload endpoint.
     const API: &str;
     type Response: DeserializeOwned;
     type Output: TryFrom<Self::Response, Error = MainError>;
@@ -53,24 +51,17 @@ pub trait UploadApi: Sized {
     fn upload(
         self,
         client: &Client,
+        config: &Config,
         token: String,
     ) -> impl Future<Output = Result<Self::Output, MainError>> {
         async move {
-            client
-                .post(Self::API)
-                .multipart(self.configure(Form::new().text("token", token)).await?)
-                .send()
-                .await
-                .and_then(Response::error_for_status)
-                .map_err(MainError::ExecuteRequest)?
-                .text()
-                .await
-                .map_err(MainError::ExecuteRequest)
-                .and_then(|response| {
-                    serde_json::from_str(&response)
-                        .map_err(|error| MainError::DecodeResponse(error, response))
-                })
-                .and_then(<Self::Output as TryFrom<Self::Response>>::try_from)
+            api::send(
+                client
+                    .post(format!("{}{}", config.upload(), Self::API))
+                    .multipart(self.configure(Form::new().text("token", token)).await?),
+            )
+            .await
+            .and_then(<Self::Output as TryFrom<Self::Response>>::try_from)
         }
     }
 }
@@ -86,15 +77,18 @@ impl TryFrom<UploadImagesResponse> for Vec<String> {
     fn try_from(
         UploadImagesResponse { message, urls }: UploadImagesResponse,
     ) -> Result<Vec<String>, MainError> {
-        urls.ok_or(message).map_err(MainError::Server)
+        urls.ok_or(message)
+            .map_err(|message| MainError::Server(None, message))
     }
 }
 pub struct UploadImages<I>(I)
 where
-    I: IntoIterator<Item = String>;
+    I: IntoIterator,
+    I::Item: AsRef<Path>;
 impl<I> UploadImages<I>
 where
-    I: IntoIterator<Item = String>,
+    I: IntoIterator,
+    I::Item: AsRef<Path>,
 {
     pub const fn new(iter: I) -> Self {
         Self(iter)
@@ -102,14 +96,15 @@ where
 }
 impl<I> UploadApi for UploadImages<I>
 where
-    I: IntoIterator<Item = String>,
+    I: IntoIterator,
+    I::Item: AsRef<Path>,
 {
-    const API: &'static str = "https://express.neighborhood.hackclub.com/upload-images";
+    const API: &'static str = "/upload-images";
     type Response = UploadImagesResponse;
     type Output = Vec<String>;
 
     async fn configure(self, mut form: Form) -> Result<Form, MainError> {
-        let mut files = stream::unfold(
+        let files = stream::unfold(
             self.0.into_iter().map(path_to_part),
             |mut iter| async move {
                 match iter.next() {
@@ -139,7 +134,8 @@ impl TryFrom<UploadVideoResponse> for String {
     fn try_from(
         UploadVideoResponse { message, url }: UploadVideoResponse,
     ) -> Result<String, MainError> {
-        url.ok_or(message).map_err(MainError::Server)
+        url.ok_or(message)
+            .map_err(|message| MainError::Server(None, message))
     }
 }
 pub struct UploadVideo<'a>(&'a str);
@@ -149,7 +145,7 @@ impl<'a> UploadVideo<'a> {
     }
 }
 impl UploadApi for UploadVideo<'_> {
-    const API: &'static str = "https://express.neighborhood.hackclub.com/upload-video";
+    const API: &'static str = "/upload-video";
     type Response = UploadVideoResponse;
     type Output = String;
 
@@ -158,13 +154,65 @@ impl UploadApi for UploadVideo<'_> {
     }
 }
 
-pub fn execute(mut args: ArgMatches, name: &str) -> Result<(), MainError> {
-    let message = args.remove_one::<String>("message").unwrap();
-    let (subcommand, args) = args.remove_subcommand().unwrap();
+/// Send a queued operation of `project`.
+pub fn replay(project: &str, operation: &mut Operation, config: &Config) -> Result<(), MainError> {
+    match operation {
+        Operation::Devlog {
+            message,
+            photobooth,
+            demo,
+            commit,
+        } => devlog::post(
+            config,
+            project,
+            message,
+            photobooth,
+            demo,
+            commit.as_deref(),
+        ),
+        Operation::Release {
+            payload,
+            screenshots,
+            check_links,
+        } => release::ship(config, project, payload, screenshots, check_links),
+    }
+}
+
+pub fn execute(mut args: ArgMatches, config: &Config, name: &str) -> Result<(), MainError> {
+    let (subcommand, subcommand_args) = args.remove_subcommand().unwrap();
+    if subcommand == "devlog"
+        && matches!(subcommand_args.subcommand_name(), Some("drafts" | "post"))
+    {
+        if message::given(&args) {
+            log::warn!("ignoring the message as drafts already have one");
+        }
+        return devlog::drafts::execute(subcommand_args, config, name);
+    }
+
+    // `devlog draft` takes the same arguments as `devlog`
+    let devlog_args = match subcommand_args.subcommand() {
+        Some(("draft", args)) => args,
+        _ => &subcommand_args,
+    };
+    let prefill = match subcommand.as_str() {
+        "devlog" => devlog::from_git(devlog_args, name)?,
+        _ => None,
+    };
+    let no_confirm = subcommand_args
+        .try_get_one::<bool>("no-confirm")
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or_default()
+        || config.no_confirm();
+    let Some(message) = message::get(&mut args, devlog_args, config, no_confirm, prefill)? else {
+        return Ok(());
+    };
+    let args = subcommand_args;
 
     match subcommand.as_str() {
-        "devlog" => devlog::execute(args, name, message.as_str()),
-        "release" | "ship" => release::execute(args, name, message),
+        "devlog" => devlog::execute(args, config, name, message),
+        "release" | "ship" => release::execute(args, config, name, message),
         _ => unreachable!(),
     }
 }
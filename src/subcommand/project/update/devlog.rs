@@ -1,22 +1,114 @@
+pub mod drafts;
+
 use {
     crate::{
         MainError,
-        api::MessageResponse,
-        cache::read_token,
+        api::{self, MessageResponse},
+        autofill,
+        cache::{DEVLOG_COMMITS, read_token, write_file},
+        config::Config,
+        history::{self, Devlog, Video},
+        outbox::{self, Media, Operation, Source},
         subcommand::project::update::{UploadApi, UploadVideo},
     },
+    chrono::Utc,
     clap::ArgMatches,
     futures_lite::stream::{Stream, StreamExt},
     pin_project_lite::pin_project,
-    reqwest::{Client, Response},
+    reqwest::Client,
     serde::Serialize,
     std::{
+        borrow::Cow,
+        fs,
+        path::PathBuf,
         pin::{Pin, pin},
         task::{Context, Poll},
     },
     tokio::runtime,
 };
 
+/// The most commits listed when there is no commit to start from.
+const MAX_COMMITS: &str = "50";
+
+fn last_commit_path(project: &str) -> Result<PathBuf, MainError> {
+    DEVLOG_COMMITS
+        .as_ref()
+        .ok_or(MainError::GetCache)
+        .map(|dir| dir.join(project))
+}
+
+/// Resolve `reference` to the hash of a commit.
+fn commit(reference: &str) -> Option<String> {
+    autofill::git([
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{reference}^{{commit}}"),
+    ])
+}
+
+/// List the subjects of the commits since `--from-git` or the last devlog of `project`, if
+/// `--from-git` was passed.
+pub fn from_git(args: &ArgMatches, project: &str) -> Result<Option<String>, MainError> {
+    if !args.contains_id("from-git") {
+        return Ok(None);
+    }
+
+    let since = match args.get_one::<String>("from-git") {
+        Some(reference) => {
+            Some(commit(reference).ok_or_else(|| MainError::UnknownGitRef(reference.clone()))?)
+        }
+        None => last_commit_path(project)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|last| {
+                let commit = commit(last.trim());
+                if commit.is_none() {
+                    log::warn!(
+                        "the commit of the last devlog is not in this repository, listing the last {MAX_COMMITS} commits"
+                    );
+                }
+                commit
+            }),
+    };
+
+    let subjects = match since {
+        Some(since) => autofill::git([
+            "log",
+            "--reverse",
+            "--format=- %s",
+            &format!("{since}..HEAD"),
+        ]),
+        None => autofill::git([
+            "log",
+            "--reverse",
+            "--format=- %s",
+            "--max-count",
+            MAX_COMMITS,
+        ]),
+    };
+    if subjects.is_none() {
+        log::warn!("there are no commits since the last devlog");
+    }
+
+    Ok(Some(subjects.unwrap_or_default()))
+}
+
+/// The commit `HEAD` is at, which is kept with a devlog when it is written as it may be posted
+/// later.
+pub fn head() -> Option<String> {
+    autofill::git(["rev-parse", "HEAD"])
+}
+
+/// Remember the commit of a devlog, so that the next `--from-git` starts from there.
+fn record_commit(project: &str, commit: &str) {
+    if let Err(error) =
+        last_commit_path(project).and_then(|path| write_file(Cow::Owned(path), commit.as_bytes()))
+    {
+        log::warn!("failed to record the commit of this devlog: {error}");
+    }
+}
+
 pub trait FutureExt: Future {
     fn map<F, T>(self, f: F) -> impl Future<Output = T>
     where
@@ -72,14 +164,14 @@ where
         let this = self.project();
 
         match this.completion_status {
-            CompletionStatus::Left => {
+            CompletionStatus::Left => this.r.poll(ctx).map(|output| {
                 *this.completion_status = CompletionStatus::Both;
-                this.r.poll(ctx).map(Some)
-            }
-            CompletionStatus::Right => {
+                Some(output)
+            }),
+            CompletionStatus::Right => this.l.poll(ctx).map(|output| {
                 *this.completion_status = CompletionStatus::Both;
-                this.l.poll(ctx).map(Some)
-            }
+                Some(output)
+            }),
             CompletionStatus::Both => Poll::Ready(None),
             CompletionStatus::None => {
                 if let output @ Poll::Ready(_) = this.l.poll(ctx).map(Some) {
@@ -106,84 +198,309 @@ where
     }
 }
 
-pub fn execute(mut args: ArgMatches, name: &str, message: &str) -> Result<(), MainError> {
-    let photobooth = args.remove_one::<String>("photobooth").unwrap();
-    let demo = args.remove_one::<String>("demo").unwrap();
-
-    let token = read_token()?;
-
-    let runtime = runtime::Builder::new_current_thread()
-        .enable_io()
-        .enable_time()
-        .build()
-        .map_err(MainError::CreateRuntime)?;
-
-    let client = Client::builder().build().map_err(MainError::CreateClient)?;
+/// Upload the video from `source`, unless it is already a url.
+async fn upload(
+    source: Option<&Source>,
+    client: &Client,
+    config: &Config,
+    token: String,
+) -> Result<Option<String>, MainError> {
+    match source {
+        None => Ok(None),
+        Some(Source::Url(url)) => Ok(Some(url.clone())),
+        Some(Source::File(media)) => {
+            let path = media.check()?.to_string_lossy();
+            UploadVideo::new(&path)
+                .upload(client, config, token)
+                .await
+                .map(Some)
+        }
+    }
+}
 
+/// Upload the videos left in the devlog and post it, returning the response of the backend.
+///
+/// Uploaded files are replaced with their urls as soon as they are uploaded, so that a devlog that
+/// fails to post does not upload them again when it is retried.
+async fn submit(
+    client: &Client,
+    config: &Config,
+    token: &str,
+    name: &str,
+    message: &str,
+    photobooth_source: &mut Option<Source>,
+    demo_source: &mut Option<Source>,
+) -> Result<String, MainError> {
     enum Url {
         Photobooth,
         Demo,
     }
 
-    let [photobooth, demo] = [
-        (photobooth.as_str(), Url::Photobooth),
-        (demo.as_str(), Url::Demo),
-    ]
-    .map(|(path, ty)| (UploadVideo::new(path).upload(&client, token.clone()), ty))
-    .map(|(fut, ty)| fut.map(move |url| (url, ty)));
+    let (photobooth, demo) = {
+        let [photobooth, demo] = [
+            (photobooth_source.as_ref(), Url::Photobooth),
+            (demo_source.as_ref(), Url::Demo),
+        ]
+        .map(|(source, ty)| (upload(source, client, config, token.to_string()), ty))
+        .map(|(fut, ty)| fut.map(move |url| (url, ty)));
 
-    let (photobooth, demo) = runtime.block_on(async move {
-        let mut fut = Both::new(photobooth, demo);
+        // both uploads are awaited even if one fails, so that the other one is kept
+        let fut = Both::new(photobooth, demo);
         let mut fut = pin!(fut);
-        let fst = match fut.next().await {
-            Some((Ok(data), url)) => (data, url),
-            Some((Err(error), _)) => return Err(error),
-            _ => unreachable!(),
-        };
-        let snd = match fut.next().await {
-            Some((Ok(data), url)) => (data, url),
-            Some((Err(error), _)) => return Err(error),
-            _ => unreachable!(),
-        };
-
-        match (fst, snd) {
-            ((photobooth, Url::Photobooth), (demo, Url::Demo))
-            | ((demo, Url::Demo), (photobooth, Url::Photobooth)) => Ok((photobooth, demo)),
+        match (fut.next().await, fut.next().await) {
+            (Some((photobooth, Url::Photobooth)), Some((demo, Url::Demo)))
+            | (Some((demo, Url::Demo)), Some((photobooth, Url::Photobooth))) => (photobooth, demo),
             _ => unreachable!(),
         }
-    })?;
+    };
+    [(photobooth_source, &photobooth), (demo_source, &demo)]
+        .into_iter()
+        .for_each(|(source, url)| {
+            if let Ok(Some(url)) = url {
+                *source = Some(Source::Url(url.clone()));
+            }
+        });
+    let (photobooth, demo) = (photobooth?, demo?);
 
     #[derive(Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct PostDevlog<'a> {
-        demo_video: &'a str,
-        photobooth_video: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        demo_video: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        photobooth_video: Option<&'a str>,
         description: &'a str,
         neighbor: &'a str,
         app: &'a str,
     }
 
-    runtime.block_on(async {
+    api::send(
         client
-            .post("https://neighborhood.hackclub.com/api/postDevlog")
+            .post(format!("{}/api/postDevlog", config.api()))
             .json(&PostDevlog {
-                demo_video: &demo,
-                photobooth_video: &photobooth,
+                demo_video: demo.as_deref(),
+                photobooth_video: photobooth.as_deref(),
                 description: message,
-                neighbor: &token,
+                neighbor: token,
                 app: name,
+            }),
+    )
+    .await
+    .map(|MessageResponse { message }| message)
+}
+
+/// Upload the videos and post the devlog, keeping track of the uploaded videos in `photobooth` and
+/// `demo`.
+///
+/// `commit` is where `HEAD` was when the devlog was written, if it was written in a repository.
+pub fn post(
+    config: &Config,
+    name: &str,
+    message: &str,
+    photobooth: &mut Option<Source>,
+    demo: &mut Option<Source>,
+    commit: Option<&str>,
+) -> Result<(), MainError> {
+    let token = read_token()?;
+
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()
+        .map_err(MainError::CreateRuntime)?;
+
+    let client = Client::builder().build().map_err(MainError::CreateClient)?;
+
+    // the files are replaced with their urls, but the history keeps where they came from
+    let sources = [photobooth.clone(), demo.clone()];
+    let response = runtime.block_on(submit(
+        &client, config, &token, name, message, photobooth, demo,
+    ))?;
+
+    println!("{response}");
+    if let Some(commit) = commit {
+        record_commit(name, commit);
+    }
+
+    let [photobooth_video, demo_video] =
+        [(&sources[0], &*photobooth), (&sources[1], &*demo)].map(|(source, uploaded)| {
+            match (source, uploaded) {
+                (Some(source), Some(Source::Url(url))) => Some(Video::new(source, url.clone())),
+                _ => None,
+            }
+        });
+    let devlog = Devlog {
+        time: Utc::now(),
+        message: message.to_string(),
+        response,
+        demo: demo_video,
+        photobooth: photobooth_video,
+    };
+    if let Err(error) = history::record_devlog(name, &devlog, config) {
+        log::warn!("failed to record the devlog in the history: {error}");
+    }
+
+    Ok(())
+}
+
+/// A video of a devlog, as given in the arguments or a draft.
+pub struct VideoArg {
+    pub path: Option<PathBuf>,
+    pub url: Option<String>,
+}
+impl VideoArg {
+    /// Remove the path and url of the video `id` from the arguments.
+    fn from_args(args: &mut ArgMatches, id: &str) -> Self {
+        Self {
+            path: args.remove_one::<String>(id).map(PathBuf::from),
+            url: args.remove_one::<String>(&format!("{id}-url")),
+        }
+    }
+
+    /// Hash the file to upload, or use the url as is.
+    fn source(&self) -> Result<Option<Source>, MainError> {
+        match (&self.path, &self.url) {
+            (Some(path), _) => Media::new(path).map(Source::File).map(Some),
+            (None, Some(url)) => Ok(Some(Source::Url(url.clone()))),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Post the devlog, or save it as a draft if the `draft` subcommand was used.
+pub fn execute(
+    mut args: ArgMatches,
+    config: &Config,
+    name: &str,
+    message: String,
+) -> Result<(), MainError> {
+    let (draft, mut args) = match args.remove_subcommand() {
+        Some((_, args)) => (true, args),
+        None => (false, args),
+    };
+    let photobooth = VideoArg::from_args(&mut args, "photobooth");
+    let demo = VideoArg::from_args(&mut args, "demo");
+
+    match draft {
+        true => drafts::save(name, message, photobooth, demo),
+        false => send(config, name, message, &photobooth, &demo, head()),
+    }
+}
+
+/// Post the devlog, or queue it if the network is down.
+fn send(
+    config: &Config,
+    name: &str,
+    message: String,
+    photobooth: &VideoArg,
+    demo: &VideoArg,
+    commit: Option<String>,
+) -> Result<(), MainError> {
+    let operation = Operation::Devlog {
+        message,
+        photobooth: photobooth.source()?,
+        demo: demo.source()?,
+        commit,
+    };
+    outbox::send(name, operation, config, super::replay)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            io::{BufRead, BufReader, Read, Write},
+            net::TcpListener,
+            sync::{
+                Arc,
+                atomic::{AtomicUsize, Ordering},
+            },
+            thread,
+        },
+    };
+
+    /// Serve uploads and fail to post the first devlog, returning the address of the server and
+    /// the number of uploads.
+    fn serve() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let uploads = Arc::new(AtomicUsize::new(0));
+
+        let counter = Arc::clone(&uploads);
+        thread::spawn(move || {
+            let mut posts = 0;
+            listener.incoming().flatten().for_each(|mut stream| {
+                let mut reader = BufReader::new(&stream);
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                let mut length = 0;
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|_| line != "\r\n") {
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                    line.clear();
+                }
+                reader.read_exact(&mut vec![0; length]).unwrap();
+
+                let (status, body) = if request.contains("/upload-video") {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    ("200 OK", r#"{"url":"https://example.com/photobooth.mp4"}"#)
+                } else {
+                    posts += 1;
+                    match posts {
+                        1 => ("500 Internal Server Error", r#"{"message":"try again"}"#),
+                        _ => ("200 OK", r#"{"message":"posted"}"#),
+                    }
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
             })
-            .send()
-            .await
-            .and_then(Response::error_for_status)
-            .map_err(MainError::ExecuteRequest)?
-            .text()
-            .await
-            .map_err(MainError::ExecuteRequest)
-            .and_then(|response| {
-                serde_json::from_str(&response)
-                    .map_err(|error| MainError::DecodeResponse(error, response))
-            })
-            .map(|MessageResponse { message }| println!("{message}"))
-    })
+        });
+
+        (address, uploads)
+    }
+
+    #[test]
+    fn retry_without_uploading() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photobooth.mp4");
+        fs::write(&path, "video").unwrap();
+
+        let (address, uploads) = serve();
+        let config = Config::with_endpoints(&address);
+        let client = Client::new();
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+        let mut photobooth = Some(Source::File(Media::new(&path).unwrap()));
+        let mut demo = Some(Source::Url("https://example.com/demo.mp4".to_string()));
+        let mut submit = || {
+            runtime.block_on(submit(
+                &client,
+                &config,
+                "token",
+                "project",
+                "message",
+                &mut photobooth,
+                &mut demo,
+            ))
+        };
+
+        assert!(matches!(submit(), Err(MainError::Server(..))));
+        assert_eq!(uploads.load(Ordering::SeqCst), 1);
+        assert_eq!(submit().unwrap(), "posted");
+        assert_eq!(uploads.load(Ordering::SeqCst), 1);
+        assert!(
+            matches!(photobooth, Some(Source::Url(url)) if url == "https://example.com/photobooth.mp4")
+        );
+    }
 }
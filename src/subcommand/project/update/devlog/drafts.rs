@@ -0,0 +1,206 @@
+//! Devlogs saved to be posted later
+
+use {
+    super::VideoArg,
+    crate::{
+        MainError,
+        cache::{DRAFTS, write_file},
+        config::Config,
+        history,
+    },
+    chrono::{DateTime, Utc},
+    clap::ArgMatches,
+    serde::{Deserialize, Serialize},
+    std::{
+        borrow::Cow,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct Draft {
+    pub time: DateTime<Utc>,
+    pub message: String,
+    pub photobooth: Option<PathBuf>,
+    pub photobooth_url: Option<String>,
+    pub demo: Option<PathBuf>,
+    pub demo_url: Option<String>,
+    /// The commit `HEAD` was at when the draft was saved.
+    pub commit: Option<String>,
+}
+
+fn dir(project: &str) -> Result<PathBuf, MainError> {
+    DRAFTS
+        .as_ref()
+        .ok_or(MainError::GetCache)
+        .map(|drafts| drafts.join(project).join("devlogs"))
+}
+
+/// Save a devlog with absolute paths, so that it can be posted from anywhere.
+pub fn save(
+    project: &str,
+    message: String,
+    photobooth: VideoArg,
+    demo: VideoArg,
+) -> Result<(), MainError> {
+    let [photobooth, demo] = [photobooth, demo].map(|VideoArg { path, url }| {
+        path.map(|path| {
+            fs::canonicalize(&path).map_err(|error| MainError::ReadFile(error, Cow::Owned(path)))
+        })
+        .transpose()
+        .map(|path| (path, url))
+    });
+    let ((photobooth, photobooth_url), (demo, demo_url)) = (photobooth?, demo?);
+    let draft = Draft {
+        time: Utc::now(),
+        message,
+        photobooth,
+        photobooth_url,
+        demo,
+        demo_url,
+        commit: super::head(),
+    };
+
+    let dir = dir(project)?;
+    let mut path = dir.join(draft.time.timestamp_millis().to_string());
+    path.set_extension("json");
+    let contents = serde_json::to_vec_pretty(&draft).expect("drafts should serialize");
+    write_file(Cow::Owned(path), &contents)?;
+
+    eprintln!(
+        "Saved the draft, post it with `neighborhood_cli project {project} update devlog post {}`",
+        read(&dir)?.len()
+    );
+    Ok(())
+}
+
+/// Read every draft in `dir` alongside its path, from oldest to newest.
+fn read(dir: &Path) -> Result<Vec<(PathBuf, Draft)>, MainError> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = fs::read_dir(dir)
+        .map_err(|error| MainError::ReadDirectory(error, Cow::Owned(dir.to_path_buf())))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<i64>().ok())
+                .map(|time| (time, path))
+        })
+        .collect::<Vec<_>>();
+    paths.sort_unstable_by_key(|(time, _)| *time);
+
+    paths
+        .into_iter()
+        .map(|(_, path)| {
+            fs::read(&path)
+                .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.clone())))
+                .and_then(|draft| {
+                    serde_json::from_slice(&draft)
+                        .map_err(|error| MainError::ParseDraft(error, Cow::Owned(path.clone())))
+                })
+                .map(|draft| (path, draft))
+        })
+        .collect()
+}
+
+fn remove(path: &Path) {
+    if let Err(error) = fs::remove_file(path) {
+        log::warn!(
+            "failed to remove the posted draft `{}`: {error}",
+            path.display()
+        );
+    }
+}
+
+pub fn execute(mut args: ArgMatches, config: &Config, name: &str) -> Result<(), MainError> {
+    let drafts = read(&dir(name)?)?;
+
+    let (subcommand, mut args) = args.remove_subcommand().unwrap();
+    match subcommand.as_str() {
+        "drafts" => drafts.iter().enumerate().for_each(|(i, (_, draft))| {
+            println!(
+                "{}: {} {}",
+                i + 1,
+                draft.time.format("%Y-%m-%d %H:%M:%S UTC"),
+                draft.message.lines().next().unwrap_or_default()
+            )
+        }),
+        "post" => {
+            let n = args.remove_one::<usize>("draft").unwrap();
+            let (path, draft) = history::nth(&drafts, n).ok_or(MainError::UnknownDraft(n))?;
+
+            super::send(
+                config,
+                name,
+                draft.message.clone(),
+                &VideoArg {
+                    path: draft.photobooth.clone(),
+                    url: draft.photobooth_url.clone(),
+                },
+                &VideoArg {
+                    path: draft.demo.clone(),
+                    url: draft.demo_url.clone(),
+                },
+                draft.commit.clone(),
+            )?;
+            remove(path);
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, chrono::TimeDelta, tempfile::tempdir};
+
+    #[test]
+    fn oldest_first() {
+        let dir = tempdir().unwrap();
+        let time = Utc::now();
+        // `1000` sorts before `999` as a string
+        [
+            ("second", time, 1000),
+            ("first", time - TimeDelta::days(1), 999),
+        ]
+        .into_iter()
+        .for_each(|(message, time, stem)| {
+            let draft = Draft {
+                time,
+                message: message.to_string(),
+                photobooth: None,
+                photobooth_url: Some("https://example.com/photobooth.mp4".to_string()),
+                demo: Some(PathBuf::from("/demo.mp4")),
+                demo_url: None,
+                commit: None,
+            };
+            fs::write(
+                dir.path().join(format!("{stem}.json")),
+                serde_json::to_vec(&draft).unwrap(),
+            )
+            .unwrap();
+        });
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let drafts = read(dir.path()).unwrap();
+        assert_eq!(
+            drafts
+                .iter()
+                .map(|(_, draft)| draft.message.as_str())
+                .collect::<Vec<_>>(),
+            ["first", "second"]
+        );
+        assert_eq!(drafts[0].1.demo.as_deref(), Some(Path::new("/demo.mp4")));
+        assert!(read(&dir.path().join("missing")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+//! Getting the message of an update from an argument, a file, stdin or an editor
+
+use {
+    crate::{MainError, config::Config, editor::Editor},
+    clap::ArgMatches,
+    std::{
+        borrow::Cow,
+        fs,
+        io::{self, Read},
+        path::PathBuf,
+    },
+};
+
+const INITIAL_MESSAGE: &str = "
+# What changed between this and the last version, markdown can be used
+# Lines starting with `#` are ignored, indent headings with a space to keep them
+# The update is cancelled if the message is empty
+";
+
+/// The template with `message` already written in it.
+fn prefilled(message: &str) -> String {
+    format!("{message}\n{INITIAL_MESSAGE}")
+}
+
+/// Remove the comments of the template, the rest is used as the message.
+fn validate(message: &str) -> Result<String, &'static str> {
+    let message = message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    match message.trim() {
+        "" => Err("the message should not be empty"),
+        message => Ok(message.to_string()),
+    }
+}
+
+/// Trim `message`, which should not be empty.
+fn non_empty(message: String) -> Result<String, MainError> {
+    let message = message.trim();
+    match message.is_empty() {
+        true => Err(MainError::EmptyMessage),
+        false => Ok(message.to_string()),
+    }
+}
+
+/// Whether a message was given in the arguments of `update`.
+pub fn given(args: &ArgMatches) -> bool {
+    ["message", "message-flag", "message-file"]
+        .into_iter()
+        .any(|id| args.contains_id(id))
+}
+
+/// Get the message from the arguments of `update`, opening an editor with the arguments of its
+/// subcommand if none was given, starting from `prefill` if it is set.
+///
+/// `-` reads the message from stdin. [None] is returned if the editor was closed without a message.
+/// The message is used without asking if `no_confirm` is set.
+pub fn get(
+    args: &mut ArgMatches,
+    subcommand_args: &ArgMatches,
+    config: &Config,
+    no_confirm: bool,
+    prefill: Option<String>,
+) -> Result<Option<String>, MainError> {
+    if prefill.is_some() && given(args) {
+        log::warn!("ignoring the generated message as one was given");
+    }
+
+    if let Some(message) = args
+        .remove_one::<String>("message")
+        .or_else(|| args.remove_one::<String>("message-flag"))
+    {
+        return match message.as_str() {
+            "-" => {
+                let mut message = String::new();
+                io::stdin()
+                    .read_to_string(&mut message)
+                    .map_err(MainError::ReadLine)?;
+                non_empty(message)
+            }
+            _ => non_empty(message),
+        }
+        .map(Some);
+    }
+    if let Some(path) = args.remove_one::<PathBuf>("message-file") {
+        return fs::read_to_string(&path)
+            .map_err(|error| MainError::ReadFile(error, Cow::Owned(path)))
+            .and_then(non_empty)
+            .map(Some);
+    }
+
+    // the editor arguments are left for the subcommand, which may also open an editor
+    let editor = Editor::from_args(&mut subcommand_args.clone(), config)?;
+    editor.edit(
+        "message.md",
+        &prefill.map_or(Cow::Borrowed(INITIAL_MESSAGE), |prefill| {
+            Cow::Owned(prefilled(&prefill))
+        }),
+        None,
+        (!no_confirm).then_some("Use this message:"),
+        validate,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template() {
+        assert!(validate(INITIAL_MESSAGE).is_err());
+
+        let message = format!(" # Title\n\nSome `code` and a ''' quote\n{INITIAL_MESSAGE}");
+        assert_eq!(
+            validate(&message).unwrap(),
+            "# Title\n\nSome `code` and a ''' quote"
+        );
+
+        assert_eq!(
+            validate(&prefilled("- first\n- it's")).unwrap(),
+            "- first\n- it's"
+        );
+    }
+}
@@ -1,64 +1,55 @@
+mod links;
+mod migrate;
+mod overrides;
+mod resolve;
+
 use {
+    self::overrides::Overrides,
     crate::{
         MainError,
-        api::MessageResponse,
-        cache::{RELEASE, get_project_token, read_token, write_file},
-        env,
+        api::{self, MessageResponse},
+        autofill,
+        cache::{get_project_token, read_token, release_form},
+        config::{Config, PROJECT_DIR},
+        crypt,
+        editor::{Editor, confirm},
+        form::{
+            check_zip_code, deserialize_non_empty_string, deserialize_url,
+            deserialize_vec_image_path, deserialize_vec_url,
+        },
+        history::{self, Release},
+        outbox::{self, Media, Operation},
+        profile::{self, Profile},
+        schema,
         subcommand::project::update::{UploadApi, UploadImages},
     },
     clap::ArgMatches,
-    reqwest::{Client, Response},
-    serde::{Deserialize, Deserializer, Serialize, Serializer},
+    reqwest::Client,
+    schemars::JsonSchema,
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
     std::{
         borrow::Cow,
-        ffi::OsString,
-        fs::{self, DirBuilder},
-        io::stdin,
-        path::PathBuf,
-        process::{Command, Stdio},
+        fs, mem,
+        path::{Path, PathBuf},
     },
-    tempfile::tempdir,
     tokio::runtime,
-    toml_edit::{Date, DocumentMut, Formatted, Item, TomlError, Value},
+    toml_edit::{DocumentMut, Item, TomlError},
 };
 
-const ERROR: &str = "string cannot be empty";
-
-pub fn serialize_iso_8601<S>(date: &Date, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_str(&date.to_string())
-}
-
-pub fn deserialize_vec_non_empty_string<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let vec = Vec::<String>::deserialize(deserializer)?;
+/// Every key of [ReleaseConfig] that can be set in the form.
+const FIELDS: &[&str] = &[
+    "codeUrl",
+    "description",
+    "playableUrl",
+    "screenshots",
+    "newScreenshotPaths",
+    "howCanWeImprove",
+    "howDidYouHear",
+    "whatAreWeDoingWell",
+];
 
-    if let Some((i, _)) = vec.iter().enumerate().find(|(_, string)| string.is_empty()) {
-        Err(<D::Error as serde::de::Error>::custom(format!(
-            "{ERROR}: screenshots[{i}]"
-        )))
-    } else {
-        Ok(vec)
-    }
-}
-
-pub fn deserialize_non_empty_string<'de, D>(deserializer: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let string = String::deserialize(deserializer)?;
-    if string.is_empty() {
-        Err(<D::Error as serde::de::Error>::custom(ERROR))
-    } else {
-        Ok(string)
-    }
-}
-
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, JsonSchema, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ReleaseConfig {
     #[serde(skip_deserializing)]
@@ -68,54 +59,83 @@ struct ReleaseConfig {
 
     #[serde(skip_deserializing)]
     changes_made: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
+    /// The link to your repository
+    #[serde(deserialize_with = "deserialize_url")]
+    #[schemars(url, pattern("^https?://"))]
     code_url: String,
+    /// Project description
     #[serde(deserialize_with = "deserialize_non_empty_string")]
+    #[schemars(length(min = 1))]
     description: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
+    /// Link that showcases your project. Can be a live demo like a website or a release link
+    #[serde(deserialize_with = "deserialize_url")]
+    #[schemars(url, pattern("^https?://"))]
     playable_url: String,
 
-    #[serde(deserialize_with = "deserialize_vec_non_empty_string")]
+    /// An array of urls pointing to screenshots
+    #[serde(deserialize_with = "deserialize_vec_url")]
+    #[schemars(inner(url, pattern("^https?://")))]
     screenshots: Vec<String>,
-    #[expect(dead_code)]
+    /// An array of paths to images that will be uploaded and then added to `screenshots`
     #[serde(
-        deserialize_with = "deserialize_vec_non_empty_string",
-        skip_serializing,
-        skip_deserializing
+        default,
+        deserialize_with = "deserialize_vec_image_path",
+        skip_serializing
     )]
-    new_screenshot_paths: Vec<String>,
+    #[schemars(inner(length(min = 1)))]
+    new_screenshot_paths: Vec<PathBuf>,
 
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    address_line_1: String,
-    address_line_2: String,
-    #[serde(serialize_with = "serialize_iso_8601")]
-    birthday: Date,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    city: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    country: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    email: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    github_username: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    first_name: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    last_name: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    state_province: String,
-    #[serde(deserialize_with = "deserialize_non_empty_string")]
-    zip_code: String,
+    /// Filled in from the profile and any overrides in this form.
+    #[serde(skip_deserializing, flatten)]
+    profile: Profile,
 
+    /// How can we improve
     #[serde(deserialize_with = "deserialize_non_empty_string")]
+    #[schemars(length(min = 1))]
     how_can_we_improve: String,
+    /// How did you hear about neighborhood
     #[serde(deserialize_with = "deserialize_non_empty_string")]
+    #[schemars(length(min = 1))]
     how_did_you_hear: String,
+    /// What are we doing well
     #[serde(deserialize_with = "deserialize_non_empty_string")]
+    #[schemars(length(min = 1))]
     what_are_we_doing_well: String,
 }
 
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[schemars(inline)]
+#[expect(dead_code, reason = "only used for the schema")]
+enum Include {
+    Path(PathBuf),
+    Paths(Vec<PathBuf>),
+}
+
+/// The release form, where any key of the profile can be overridden
+#[derive(JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[schemars(title = "Neighborhood release form")]
+#[expect(dead_code, reason = "only used for the schema")]
+struct Form {
+    #[serde(flatten)]
+    release: ReleaseConfig,
+    #[serde(flatten)]
+    profile: Profile,
+    /// Paths of toml files, relative to the root of the project, whose fields are used when they are not set here
+    include: Option<Include>,
+    /// Used to upgrade this form when new fields are added, do not change it
+    version: Option<u32>,
+}
+
+/// The schema of release forms.
+pub fn schema() -> Value {
+    schema::generate::<Form>()
+}
+
 const INITIAL_RELEASE_CONFIG: &str = r#"# All the fields, unless specified otherwise should contain a value
+# Empty fields are filled from the files listed in `include = ["path/to/shared.toml"]`, and
+# `{{ name }}` in any value is replaced with the variable `name` from the config or environment
 # Project
 codeUrl = "" # The link to your repository
 description = "" # Project description
@@ -125,212 +145,440 @@ playableUrl = "" # Link that showcases your project. Can be a live demo like a w
 # An array of urls pointing to screenshots
 # You should not use this to add new screenshots, instead you should only use this to remove screenshots
 screenshots = []
-# An array of paths that will be uploaded and then added to the screenshot array above
+# An array of paths to images that will be uploaded and then added to the screenshot array above
+# Relative paths are resolved against the root of your project
 # newScreenshotPaths = []
 
 # Personal information
-addressLine1 = ""
-addressLine2 = "" # Optional
-birthday = 2000-01-01 # ISO-8601 format (YYYY-MM-DD)
-city = ""
-country = "" # Country code
-email = "" # The email used to sign up into neighborhood
-githubUsername = ""
-firstName = ""
-lastName = ""
-stateProvince = ""
-zipCode = ""
+# This is read from your profile, run `neighborhood_cli profile edit` to change it
+# Any of its keys, such as `email`, can be added here to override it for this project
 
 # Telemetry
 howCanWeImprove = ""
 howDidYouHear = ""
-whatAreWeDoingWell = """#;
+whatAreWeDoingWell = ""
+
+# Used to upgrade this form when new fields are added, do not change it
+version = 1"#;
 
-fn validate(release_config: &str) -> Result<DocumentMut, TomlError> {
+/// Check the form with its includes relative to `root` and its variables resolved, returning it
+/// unresolved.
+fn validate(
+    release_config: &str,
+    root: &Path,
+    profile: &Profile,
+    config: &Config,
+) -> Result<DocumentMut, TomlError> {
     release_config.parse::<DocumentMut>().and_then(|document| {
-        toml_edit::de::from_str::<ReleaseConfig>(release_config)
-            .map(move |_| document)
+        let resolved = resolve::resolve(&document, root, |name| config.var(name))?.to_string();
+        toml_edit::de::from_str::<ReleaseConfig>(&resolved)
+            .and_then(|_| toml_edit::de::from_str::<Profile>(&resolved))
             .map_err(TomlError::from)
+            .and_then(|overrides| {
+                // a zip code in the form is checked against the country of the profile unless it is
+                // also overridden
+                overrides
+                    .country()
+                    .or(profile.country())
+                    .map_or(Ok(()), |country| check_zip_code(&resolved, country))
+            })
+            .map(move |_| document)
     })
 }
 
-pub fn execute(mut args: ArgMatches, name: &str, message: String) -> Result<(), MainError> {
-    let token = read_token()?;
-    let release = RELEASE.as_ref().ok_or(MainError::GetCache)?;
-    if !release.is_dir() {
-        DirBuilder::new()
-            .recursive(true)
-            .create(release)
-            .map_err(|error| MainError::CreateDirectory(error, Cow::Borrowed(release)))?;
+pub fn execute(
+    mut args: ArgMatches,
+    config: &Config,
+    name: &str,
+    message: String,
+) -> Result<(), MainError> {
+    // fail before the form is filled in if not logged in
+    read_token()?;
+    let release_config = release_form(name)?;
+
+    let no_confirm = args.remove_one("no-confirm").unwrap_or_default() || config.no_confirm();
+    let skip_link_check = args
+        .remove_one::<bool>("skip-link-check")
+        .unwrap_or_default();
+    let overrides = Overrides::new(
+        args.remove_one::<PathBuf>("from-file").as_deref(),
+        args.remove_many::<String>("set").into_iter().flatten(),
+    )?;
+
+    let reset = !release_config.exists() || args.remove_one("reset").unwrap_or_default();
+    let root = resolve::root(&release_config, &PROJECT_DIR, reset)?;
+    let mut contents = if reset {
+        let mut document = INITIAL_RELEASE_CONFIG
+            .parse::<DocumentMut>()
+            .expect("the initial release config should be valid toml");
+        autofill::release(&mut document);
+        schema::directive("release", &self::schema()).unwrap_or_default() + &document.to_string()
+    } else {
+        crypt::read(&release_config, config).map(migrate::upgrade)?
+    };
+    let editor = (args.remove_one("edit").unwrap_or_default()
+        || (!release_config.exists() && overrides.is_empty()))
+    .then(|| Editor::from_args(&mut args, config))
+    .transpose()?;
+    if let Some(editor) = &editor
+        && !profile::exists()
+    {
+        eprintln!("You do not have a profile yet, it will be shared between all of your projects");
+        if !profile::edit(editor, config, no_confirm, false)? {
+            return Ok(());
+        }
     }
-    let mut release_config = release.to_path_buf();
-    release_config.push(name);
-    release_config.set_extension("toml");
+    let profile = profile::read(config)?;
+
+    // overrides are only applied to the form that is shipped, so that they are not cached
+    let with_overrides = |document: &DocumentMut| {
+        let mut document = document.clone();
+        overrides.apply(&mut document);
+        document
+    };
+    let check = |contents: &str| {
+        contents.parse::<DocumentMut>().and_then(|document| {
+            validate(
+                &with_overrides(&document).to_string(),
+                &root,
+                &profile,
+                config,
+            )
+            .map(|_| document)
+        })
+    };
 
-    let no_confirm: bool = args.remove_one("no-confirm").unwrap_or_default();
+    if let Some(editor) = editor {
+        // the draft is overwritten with `contents` if it is not resumed, and it is not kept at all
+        // when encrypting as it is edited in plain text
+        let draft = (!config.encrypt()).then(|| release_config.with_extension("draft.toml"));
+        if let Some(draft) = draft.as_deref().filter(|draft| draft.is_file())
+            && (no_confirm || confirm("A draft from a previous edit was found, resume it")?)
+        {
+            contents = fs::read_to_string(draft)
+                .map_err(|error| MainError::ReadFile(error, Cow::Owned(draft.to_path_buf())))?;
+        }
 
-    if !release_config.exists() || args.remove_one("edit").unwrap_or_default() {
-        let contents = if !release_config.exists() || args.remove_one("reset").unwrap_or_default() {
-            Ok(Cow::Borrowed(INITIAL_RELEASE_CONFIG))
-        } else {
-            fs::read_to_string(&release_config).map(Cow::Owned)
-        };
+        match editor.edit(
+            &format!("{name}.toml"),
+            &contents,
+            draft.as_deref(),
+            (!no_confirm).then_some("Submit:"),
+            check,
+        )? {
+            Some(document) => Ok(document),
+            None => return Ok(()),
+        }
+    } else {
+        check(&contents).map_err(MainError::ParseReleaseConfig)
+    }
+    .and_then(|document| {
+        let resolved = resolve::resolve(&with_overrides(&document), &root, |name| config.var(name))
+            .map_err(MainError::ParseReleaseConfig)?;
+        let overrides = toml_edit::de::from_document::<Profile>(resolved.clone())
+            .map_err(TomlError::from)
+            .map_err(MainError::ParseReleaseConfig)?;
+        let mut form = toml_edit::de::from_document::<ReleaseConfig>(resolved)
+            .map_err(TomlError::from)
+            .map_err(MainError::ParseReleaseConfig)?;
+        let screenshots = mem::take(&mut form.new_screenshot_paths)
+            .iter()
+            .map(|path| Media::new(path))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        match contents {
-            Ok(contents) => {
-                let dir = tempdir().map_err(MainError::CreateTempDir)?;
+        if let Err(error) = crypt::write(
+            Cow::Owned(release_config.clone()),
+            &document.to_string(),
+            config,
+        )
+        .and_then(|_| resolve::record_root(&release_config, &root))
+        {
+            log::warn!("failed to save the release form: {error}");
+        }
+        let mut release_config = form;
+        release_config.profile = profile.merge(overrides).complete()?;
+        release_config.changes_made = message;
 
-                let mut path = PathBuf::from(dir.path());
-                path.push(name);
-                path.set_extension("toml");
+        let mut payload =
+            serde_json::to_value(&release_config).expect("release forms should serialize");
+        if let Value::Object(object) = &mut payload {
+            object.remove("token");
+        }
 
-                if let Err(error) = fs::write(&path, contents.as_ref()) {
-                    return Err(MainError::WriteFile(error, Cow::Owned(path)));
+        let mut check_links = false;
+        if !skip_link_check {
+            let runtime = runtime::Builder::new_current_thread()
+                .enable_io()
+                .enable_time()
+                .build()
+                .map_err(MainError::CreateRuntime)?;
+            match runtime.block_on(links::verify(&payload, config)) {
+                Err(error) if api::is_offline(&error) => {
+                    log::warn!("{error}, the links will be checked again before shipping");
+                    check_links = true;
                 }
-
-                let command = args
-                    .remove_one::<String>("editor")
-                    .map(OsString::from)
-                    .map(Cow::Owned)
-                    .or_else(|| {
-                        // SAFETY: this is single threaded
-                        unsafe { env::var(c"VISUAL") }
-                            .or_else(|| unsafe { env::var(c"EDITOR") })
-                            .map(Cow::Borrowed)
-                    })
-                    .ok_or(MainError::NoEditor)?;
-
-                let mut command = Command::new(command);
-                command
-                    .stdin(Stdio::inherit())
-                    .stderr(Stdio::inherit())
-                    .stdout(Stdio::inherit())
-                    .args(args.remove_many::<String>("arg").unwrap_or_default())
-                    .arg(&path);
-
-                let mut line = String::with_capacity(3);
-                'doc: loop {
-                    command.output().map_err(|error| {
-                        MainError::ExecuteCommand(error, format!("{command:?}"))
-                    })?;
-
-                    let release_config = fs::read_to_string(&path)
-                        .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.clone())))?;
-
-                    match release_config.parse::<DocumentMut>().and_then(|document| {
-                        toml_edit::de::from_str::<ReleaseConfig>(&release_config)
-                            .map(move |_| document)
-                            .map_err(TomlError::from)
-                    }) {
-                        Ok(document) => {
-                            write_file(Cow::Owned(path.clone()), release_config.as_bytes())?;
-                            if no_confirm {
-                                break 'doc Ok(document);
-                            }
-                            loop {
-                                eprintln!("{document}\nSubmit: (yes/no)?:");
-                                line.clear();
-                                stdin().read_line(&mut line).map_err(MainError::ReadLine)?;
-                                match line.trim() {
-                                    "y" | "yes" => break 'doc Ok(document),
-                                    "n" | "no" => return Ok(()),
-                                    response => eprintln!("unknown option `{response}`"),
-                                }
-                            }
-                        }
-                        Err(error) => {
-                            eprintln!("{error}");
-                            if no_confirm {
-                                return Ok(());
-                            }
-                            loop {
-                                eprintln!("Exit: (yes/no)?: ");
-                                line.clear();
-                                stdin().read_line(&mut line).map_err(MainError::ReadLine)?;
-                                match line.trim() {
-                                    "y" | "yes" => return Ok(()),
-                                    "n" | "no" => break,
-                                    response => eprintln!("unknown option `{response}`"),
-                                }
-                            }
-                        }
+                Err(error) => return Err(error),
+                Ok(broken) if !broken.is_empty() => {
+                    broken.iter().for_each(|link| eprintln!("warning: {link}"));
+                    if no_confirm {
+                        return Err(MainError::BrokenLinks(broken.len()));
+                    } else if !confirm("Ship anyway:")? {
+                        return Ok(());
                     }
                 }
+                Ok(_) => {}
             }
-            Err(error) => Err(MainError::ReadFile(
-                error,
-                Cow::Owned(release_config.clone()),
-            )),
         }
-    } else {
-        fs::read_to_string(&release_config)
-            .map_err(|error| MainError::ReadFile(error, Cow::Owned(release_config.clone())))
-            .and_then(|release_config| {
-                validate(&release_config).map_err(MainError::ParseReleaseConfig)
-            })
-    }
-    .and_then(|mut document| {
-        let runtime = runtime::Builder::new_current_thread()
-            .enable_io()
-            .enable_time()
-            .build()
-            .map_err(MainError::CreateRuntime)?;
-        let client = Client::builder().build().map_err(MainError::CreateClient)?;
-
-        if let Some(Item::Value(Value::Array(new_screenshot_paths))) =
-            document.remove("newScreenshotPaths")
+
+        outbox::send(
+            name,
+            Operation::Release {
+                payload,
+                screenshots,
+                check_links,
+            },
+            config,
+            super::replay,
+        )
+    })
+}
+
+/// Move the uploaded screenshots from `newScreenshotPaths` to `screenshots` in the cached form.
+fn add_screenshots(name: &str, uploaded: &[Media], urls: &[String], config: &Config) {
+    let result = release_form(name).and_then(|path| {
+        let root = resolve::root(&path, &PROJECT_DIR, false)?;
+        let mut document = crypt::read(&path, config)?
+            .parse::<DocumentMut>()
+            .map_err(MainError::ParseReleaseConfig)?;
+        if let Some(paths) = document
+            .get_mut("newScreenshotPaths")
+            .and_then(Item::as_array_mut)
         {
-            let request = UploadImages::new(
-                new_screenshot_paths
-                    .into_iter()
-                    .flat_map(|val| match val {
-                        Value::String(string) => Some(string),
-                        _ => None,
-                    })
-                    .map(Formatted::into_value),
-            );
-
-            let urls = runtime.block_on(request.upload(&client, token.clone()))?;
-
-            let _ = document
-                .as_item_mut()
-                .as_table_mut()
-                .and_then(|table| table.get_mut("screenshots"))
-                .map(|screenshots| {
-                    if let Item::Value(Value::Array(screenshots)) = screenshots {
-                        urls.into_iter()
-                            .filter(|url| !url.is_empty())
-                            .for_each(|url| screenshots.push(url))
-                    }
-                });
+            paths.retain(|path| {
+                !path
+                    .as_str()
+                    .and_then(|path| root.join(path).canonicalize().ok())
+                    .is_some_and(|path| uploaded.iter().any(|media| media.path == path))
+            });
+            if paths.is_empty() {
+                document.remove("newScreenshotPaths");
+            }
+        }
+        if let Some(screenshots) = document.get_mut("screenshots").and_then(Item::as_array_mut) {
+            urls.iter().for_each(|url| screenshots.push(url));
         }
 
-        let _ = write_file(Cow::Owned(release_config), document.to_string().as_bytes());
-        let mut release_config = toml_edit::de::from_document::<ReleaseConfig>(document)
-            .map_err(TomlError::from)
-            .map_err(MainError::ParseReleaseConfig)?;
-        release_config.app_id = runtime.block_on(get_project_token(Cow::Borrowed(name)))?;
-        release_config.changes_made = message;
-        release_config.token = token;
+        crypt::write(Cow::Owned(path), &document.to_string(), config)
+    });
+    if let Err(error) = result {
+        log::warn!("failed to add the uploaded screenshots to the release form: {error}");
+    }
+}
+
+/// Upload the screenshots left in a release and ship it, keeping track of the progress in
+/// `payload`, `screenshots` and `check_links` so that it can be queued again if the network goes
+/// down.
+pub fn ship(
+    config: &Config,
+    name: &str,
+    payload: &mut Value,
+    screenshots: &mut Vec<Media>,
+    check_links: &mut bool,
+) -> Result<(), MainError> {
+    let token = read_token()?;
+
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()
+        .map_err(MainError::CreateRuntime)?;
 
-        runtime.block_on(async {
+    if *check_links {
+        let broken = runtime.block_on(links::verify(payload, config))?;
+        if !broken.is_empty() {
+            broken.iter().for_each(|link| eprintln!("warning: {link}"));
+            return Err(MainError::BrokenLinks(broken.len()));
+        }
+        *check_links = false;
+    }
+    let client = Client::builder().build().map_err(MainError::CreateClient)?;
+
+    if !screenshots.is_empty() {
+        let paths = screenshots
+            .iter()
+            .map(Media::check)
+            .collect::<Result<Vec<_>, _>>()?;
+        let urls = runtime
+            .block_on(UploadImages::new(paths).upload(&client, config, token.clone()))?
+            .into_iter()
+            .filter(|url| !url.is_empty())
+            .collect::<Vec<_>>();
+
+        add_screenshots(name, screenshots, &urls, config);
+        screenshots.clear();
+        if let Some(Value::Array(screenshots)) = payload.get_mut("screenshots") {
+            screenshots.extend(urls.into_iter().map(Value::from));
+        }
+    }
+
+    if payload["appId"].as_str().is_none_or(str::is_empty) {
+        payload["appId"] =
+            Value::from(runtime.block_on(get_project_token(Cow::Borrowed(name), config))?);
+    }
+
+    let mut request = payload.clone();
+    request["token"] = Value::from(token);
+    runtime.block_on(async {
+        api::send(
             client
-                .post("https://neighborhood.hackclub.com/api/shipApp")
-                .json(&release_config)
-                .send()
-                .await
-                .and_then(Response::error_for_status)
-                .map_err(MainError::ExecuteRequest)?
-                .text()
-                .await
-                .map_err(MainError::ExecuteRequest)
-                .and_then(|response| {
-                    serde_json::from_str(&response)
-                        .map_err(|error| MainError::DecodeResponse(error, response.to_string()))
-                })
-                .map(|MessageResponse { message }| {
-                    eprintln!("{message}");
-                })
+                .post(format!("{}/api/shipApp", config.api()))
+                .json(&request),
+        )
+        .await
+        .map(|MessageResponse { message }| {
+            eprintln!("{message}");
+            let release = Release::new(payload, message, config.redact_history());
+            if let Err(error) = history::record_release(name, &release, config) {
+                log::warn!("failed to record the release in the history: {error}");
+            }
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A valid release form.
+    fn form() -> String {
+        INITIAL_RELEASE_CONFIG
+            .replace(r#""""#, r#""form""#)
+            .replace(
+                r#"codeUrl = "form""#,
+                r#"codeUrl = "https://github.com/asdfish/neighborhood_cli""#,
+            )
+            .replace(
+                r#"playableUrl = "form""#,
+                r#"playableUrl = "https://example.com""#,
+            )
+            .replace(
+                "screenshots = []",
+                r#"screenshots = ["https://example.com/a.png"]"#,
+            )
+    }
+
+    #[test]
+    fn profile_overrides() {
+        let profile = toml_edit::de::from_str::<Profile>(
+            &crate::profile::INITIAL_PROFILE
+                .replace(r#""""#, r#""profile""#)
+                .replace(r#"addressLine2 = "profile""#, r#"addressLine2 = """#)
+                .replace(r#"country = "profile""#, r#"country = "US""#)
+                .replace(r#"email = "profile""#, r#"email = "profile@example.com""#)
+                .replace(r#"zipCode = "profile""#, r#"zipCode = "12345""#),
+        )
+        .unwrap();
+
+        let release_config = form() + "\nemail = \"override@example.com\"\ncountry = \"US\"\n";
+        validate(
+            &release_config,
+            Path::new("."),
+            &Profile::default(),
+            &Config::default(),
+        )
+        .unwrap();
+        // the zip code is checked against the country of the profile
+        assert!(
+            validate(
+                &(form() + "\nzipCode = \"K1A 0B1\"\n"),
+                Path::new("."),
+                &profile,
+                &Config::default(),
+            )
+            .is_err()
+        );
+
+        let overrides = toml_edit::de::from_str::<Profile>(&release_config).unwrap();
+        let mut release_config = toml_edit::de::from_str::<ReleaseConfig>(&release_config).unwrap();
+        release_config.profile = profile.merge(overrides).complete().unwrap();
+
+        let json = serde_json::to_value(&release_config).unwrap();
+        assert_eq!(json["email"], "override@example.com");
+        assert_eq!(json["firstName"], "profile");
+        assert_eq!(json["addressLine2"], "");
+        assert_eq!(json["birthday"], "2000-01-01");
+        assert_eq!(
+            json["codeUrl"],
+            "https://github.com/asdfish/neighborhood_cli"
+        );
+
+        assert!(matches!(
+            Profile::default().merge(Profile::default()).complete(),
+            Err(MainError::IncompleteProfile("addressLine1"))
+        ));
+        let overrides = toml_edit::de::from_str::<Profile>(r#"country = "CA""#).unwrap();
+        assert!(matches!(
+            release_config.profile.merge(overrides).complete(),
+            Err(MainError::InvalidZipCode(_))
+        ));
+    }
+
+    #[test]
+    fn schema_fields() {
+        let schema = super::schema();
+        let mut properties = schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let mut fields = FIELDS
+            .iter()
+            .chain(profile::FIELDS)
+            .chain(&["include", "version"])
+            .copied()
+            .collect::<Vec<_>>();
+        properties.sort_unstable();
+        fields.sort_unstable();
+        assert_eq!(properties, fields);
+    }
+
+    #[test]
+    fn invalid_values() {
+        [
+            ("playableUrl", r#""example.com""#, "playableUrl"),
+            (
+                "screenshots",
+                r#"["https://example.com", "a.png"]"#,
+                "screenshots[1]",
+            ),
+            ("email", r#""me""#, "email"),
+            ("country", r#""USA""#, "country"),
+            ("zipCode", r#""K1A 0B1""#, "zipCode"),
+            ("birthday", "9999-01-01", "birthday"),
+            (
+                "newScreenshotPaths",
+                concat!(r#"[""#, env!("CARGO_MANIFEST_DIR"), r#"/src/main.rs"]"#),
+                "newScreenshotPaths[0]",
+            ),
+        ]
+        .into_iter()
+        .for_each(|(key, value, expected)| {
+            let mut document = (form() + "\ncountry = \"US\"\n")
+                .parse::<DocumentMut>()
+                .unwrap();
+            document.remove(key);
+            let line = format!("{key} = {value}");
+            let error = validate(
+                &format!("{document}{line}\n"),
+                Path::new("."),
+                &Profile::default(),
+                &Config::default(),
+            )
+            .unwrap_err()
+            .to_string();
+            assert!(error.contains(expected), "{error}");
+            assert!(error.contains(&line), "{error}");
+        });
+    }
+}
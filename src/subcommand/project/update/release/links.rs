@@ -0,0 +1,257 @@
+//! Checking that the links of a release can be opened before shipping it
+
+use {
+    crate::{MainError, NAME, api, config::Config},
+    reqwest::{Client, StatusCode, Url},
+    serde_json::Value,
+    std::{
+        borrow::Cow,
+        fmt::{self, Display, Formatter},
+        time::Duration,
+    },
+};
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+/// Path segments of pages asking to sign in, which reviewers will not be able to get past.
+const LOGIN_SEGMENTS: &[&str] = &[
+    "auth", "log-in", "login", "oauth", "sign-in", "sign_in", "signin", "sso",
+];
+
+#[derive(Debug)]
+pub enum Problem {
+    Status(StatusCode),
+    Login(Url),
+    Unreachable(reqwest::Error),
+}
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status(status) => write!(f, "responded with status `{status}`"),
+            Self::Login(url) => write!(f, "redirects to the login page `{url}`"),
+            Self::Unreachable(error) => write!(f, "could not be reached: {error}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BrokenLink {
+    key: Cow<'static, str>,
+    url: String,
+    problem: Problem,
+}
+impl Display for BrokenLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` ({}) {}", self.key, self.url, self.problem)
+    }
+}
+
+impl BrokenLink {
+    fn is_unreachable(&self) -> bool {
+        matches!(&self.problem, Problem::Unreachable(error) if error.is_connect() || error.is_timeout())
+    }
+}
+
+/// Whether none of the `links` could be reached, which may mean that the network is down rather
+/// than that every link is broken.
+fn offline(broken: &[BrokenLink], links: usize) -> bool {
+    links > 0 && broken.len() == links && broken.iter().all(BrokenLink::is_unreachable)
+}
+
+/// Every link of a release that reviewers will open, keyed by its field.
+fn of(release: &Value) -> impl Iterator<Item = (Cow<'static, str>, &str)> {
+    ["codeUrl", "playableUrl"]
+        .into_iter()
+        .filter_map(|key| release[key].as_str().map(|url| (Cow::Borrowed(key), url)))
+        .chain(
+            release["screenshots"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .filter_map(|(i, url)| {
+                    url.as_str()
+                        .map(|url| (Cow::Owned(format!("screenshots[{i}]")), url))
+                }),
+        )
+}
+
+fn is_login(url: &Url) -> bool {
+    url.path_segments().is_some_and(|mut segments| {
+        segments.any(|segment| LOGIN_SEGMENTS.contains(&segment.to_lowercase().as_str()))
+    })
+}
+
+async fn check_url(client: &Client, url: &str) -> Option<Problem> {
+    // some servers do not implement `HEAD` or treat it differently, so anything other than a
+    // success is retried with `GET`
+    let response = match client.head(url).send().await {
+        Ok(response) if response.status().is_success() => Ok(response),
+        _ => client.get(url).send().await,
+    };
+    match &response {
+        Ok(response) => log::debug!("checked link {url} -> {}", response.status()),
+        Err(error) => log::debug!("checked link {url} -> {error}"),
+    }
+
+    match response {
+        Err(error) => Some(Problem::Unreachable(error.without_url())),
+        Ok(response) if !response.status().is_success() => Some(Problem::Status(response.status())),
+        Ok(response)
+            if is_login(response.url())
+                && Url::parse(url).is_ok_and(|original| !is_login(&original)) =>
+        {
+            Some(Problem::Login(response.url().clone()))
+        }
+        Ok(_) => None,
+    }
+}
+
+/// Request every link, returning the ones that reviewers will not be able to open.
+pub async fn check<'a, I>(links: I) -> Result<Vec<BrokenLink>, MainError>
+where
+    I: IntoIterator<Item = (Cow<'static, str>, &'a str)>,
+{
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .user_agent(format!("{NAME}/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(MainError::CreateClient)?;
+
+    let mut broken = Vec::new();
+    for (key, url) in links {
+        if let Some(problem) = check_url(&client, url).await {
+            broken.push(BrokenLink {
+                key,
+                url: url.to_string(),
+                problem,
+            });
+        }
+    }
+    Ok(broken)
+}
+
+/// Check the links of a release, failing like any other request if none of them could be reached
+/// because the network is down.
+///
+/// The backend is requested when every link is unreachable, so that links with dead domains are
+/// still reported as broken.
+pub async fn verify(release: &Value, config: &Config) -> Result<Vec<BrokenLink>, MainError> {
+    let broken = check(of(release)).await?;
+    if offline(&broken, of(release).count()) {
+        let client = Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .map_err(MainError::CreateClient)?;
+        api::reachable(&client, config).await?;
+    }
+
+    Ok(broken)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            io::{BufRead, BufReader, Write},
+            net::TcpListener,
+            thread,
+        },
+        tokio::runtime,
+    };
+
+    /// Serve a few canned responses, returning the address of the server.
+    fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            listener.incoming().flatten().for_each(|mut stream| {
+                let mut request = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|_| line != "\r\n") {
+                    line.clear();
+                }
+
+                let (method, path) = request
+                    .split_once(' ')
+                    .and_then(|(method, rest)| Some((method, rest.split_once(' ')?.0)))
+                    .unwrap();
+                let path = path.split_once('?').map_or(path, |(path, _)| path);
+                let response = match (method, path) {
+                    ("HEAD", "/get-only") => "405 Method Not Allowed\r\n",
+                    ("GET", "/get-only") | (_, "/ok") | (_, "/login") => "200 OK\r\n",
+                    (_, "/private") => "302 Found\r\nLocation: /login?return_to=%2Fprivate\r\n",
+                    _ => "404 Not Found\r\n",
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {response}Content-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            })
+        });
+
+        address
+    }
+
+    #[test]
+    fn links() {
+        let address = serve();
+        let urls = ["/ok", "/get-only", "/login", "/missing", "/private"]
+            .map(|path| format!("{address}{path}"));
+
+        let broken = runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(check(
+                urls.iter()
+                    .map(|url| (Cow::Borrowed("playableUrl"), url.as_str()))
+                    .chain([(Cow::Borrowed("codeUrl"), "http://127.0.0.1:1/")]),
+            ))
+            .unwrap();
+
+        let broken = broken
+            .iter()
+            .map(|link| (link.url.as_str(), &link.problem))
+            .collect::<Vec<_>>();
+        assert_eq!(broken.len(), 3, "{broken:?}");
+        assert!(matches!(
+            broken[0],
+            (url, Problem::Status(StatusCode::NOT_FOUND)) if url == urls[3]
+        ));
+        assert!(matches!(broken[1], (url, Problem::Login(_)) if url == urls[4]));
+        assert!(matches!(broken[2], (_, Problem::Unreachable(_))));
+    }
+
+    #[test]
+    fn unreachable_links() {
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+        let release = serde_json::json!({
+            "codeUrl": "http://127.0.0.1:1/",
+            "playableUrl": "http://127.0.0.1:1/play",
+            "screenshots": [],
+        });
+
+        // the links are broken if the backend can be reached
+        let broken = runtime
+            .block_on(verify(&release, &Config::with_endpoints(&serve())))
+            .unwrap();
+        assert_eq!(broken.len(), 2);
+
+        let error = runtime
+            .block_on(verify(
+                &release,
+                &Config::with_endpoints("http://127.0.0.1:1"),
+            ))
+            .unwrap_err();
+        assert!(api::is_offline(&error), "{error}");
+    }
+}
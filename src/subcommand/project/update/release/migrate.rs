@@ -0,0 +1,121 @@
+//! Upgrading release forms cached by older versions, so that they do not have to be reset
+
+use {
+    super::INITIAL_RELEASE_CONFIG,
+    crate::form::set_value,
+    toml_edit::{DocumentMut, Item, Value},
+};
+
+/// The version of [INITIAL_RELEASE_CONFIG], bump it whenever a field is added to the template.
+pub const VERSION: i64 = 1;
+const VERSION_KEY: &str = "version";
+
+/// The version of a cached form, forms from before versions were added are version 0.
+fn version(document: &DocumentMut) -> i64 {
+    document
+        .get(VERSION_KEY)
+        .and_then(Item::as_integer)
+        .unwrap_or(0)
+}
+
+/// Insert every field of the template missing from `document` along with its comments, returning
+/// the keys that were added.
+///
+/// The values of fields that are already present are kept as is.
+fn migrate(document: &mut DocumentMut) -> Vec<String> {
+    let template = INITIAL_RELEASE_CONFIG
+        .parse::<DocumentMut>()
+        .expect("the initial release config should be valid toml");
+
+    let missing = template
+        .iter()
+        .filter(|(key, _)| *key != VERSION_KEY && !document.contains_key(key))
+        .filter_map(|(key, _)| template.as_table().get_key_value(key))
+        .collect::<Vec<_>>();
+    let added = missing
+        .into_iter()
+        .map(|(key, item)| {
+            document.insert_formatted(key, item.clone());
+            key.get().to_string()
+        })
+        .collect();
+
+    match document.contains_key(VERSION_KEY) {
+        true => set_value(document, VERSION_KEY, Value::from(VERSION)),
+        false => {
+            if let Some((key, item)) = template.as_table().get_key_value(VERSION_KEY) {
+                document.insert_formatted(key, item.clone());
+            }
+        }
+    }
+
+    added
+}
+
+/// Upgrade the form in `contents` if it was written by an older version.
+///
+/// Forms that cannot be parsed are returned unchanged, so that validating them reports the error.
+pub fn upgrade(contents: String) -> String {
+    let mut document = match contents.parse::<DocumentMut>() {
+        Ok(document) if version(&document) < VERSION => document,
+        Ok(document) => {
+            if version(&document) > VERSION {
+                log::warn!(
+                    "the release form was written by a newer version of this program (form version {})",
+                    version(&document)
+                );
+            }
+            return contents;
+        }
+        Err(_) => return contents,
+    };
+
+    let old = version(&document);
+    let added = migrate(&mut document);
+    if added.is_empty() {
+        eprintln!("Upgraded the release form from version {old} to {VERSION}");
+    } else {
+        eprintln!(
+            "Upgraded the release form from version {old} to {VERSION}, new fields: {}",
+            added.join(", ")
+        );
+    }
+    document.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_values() {
+        assert_eq!(
+            version(&INITIAL_RELEASE_CONFIG.parse::<DocumentMut>().unwrap()),
+            VERSION
+        );
+        let mut document = INITIAL_RELEASE_CONFIG
+            .replace(r#"description = """#, r#"description = "mine""#)
+            .parse::<DocumentMut>()
+            .unwrap();
+        document.remove(VERSION_KEY);
+        document.remove("howCanWeImprove");
+        document.insert("email", Item::Value(Value::from("me@example.com")));
+        assert_eq!(version(&document), 0);
+
+        assert_eq!(migrate(&mut document), ["howCanWeImprove"]);
+        assert_eq!(version(&document), VERSION);
+        assert_eq!(document["description"].as_str(), Some("mine"));
+        assert_eq!(document["email"].as_str(), Some("me@example.com"));
+        assert!(document.contains_key("howCanWeImprove"));
+        assert!(
+            document
+                .to_string()
+                .contains("# Telemetry\nhowCanWeImprove")
+        );
+
+        // up to date forms are left alone
+        let contents = document.to_string();
+        assert_eq!(upgrade(contents.clone()), contents);
+        assert_eq!(upgrade("not toml".to_string()), "not toml");
+    }
+}
@@ -0,0 +1,206 @@
+//! Non interactive changes to the release form from flags, files and environment variables
+
+use {
+    super::FIELDS,
+    crate::{MainError, form::set_value, profile},
+    std::{borrow::Cow, env, fs, path::Path},
+    toml_edit::{Array, DocumentMut, InlineTable, Value},
+};
+
+const ENV_PREFIX: &str = "NEIGHBORHOOD_RELEASE_";
+/// Fields that are not strings, whose values will be parsed as toml.
+const TOML_FIELDS: &[&str] = &["birthday", "screenshots", "newScreenshotPaths"];
+
+fn check_field(field: &str) -> Result<(), MainError> {
+    if FIELDS.contains(&field) || profile::FIELDS.contains(&field) {
+        Ok(())
+    } else {
+        Err(MainError::UnknownReleaseField(field.to_string()))
+    }
+}
+
+/// Parse `value` according to the type of `field`, falling back to a string.
+fn parse_value(field: &str, value: &str) -> Value {
+    TOML_FIELDS
+        .contains(&field)
+        .then(|| value.parse::<Value>().ok())
+        .flatten()
+        .unwrap_or_else(|| Value::from(value))
+}
+
+/// Convert `SCREAMING_SNAKE_CASE` environment variable suffixes into `camelCase` fields.
+fn env_to_field(var: &str) -> String {
+    var.split('_')
+        .filter(|part| !part.is_empty())
+        .enumerate()
+        .map(|(i, part)| {
+            let part = part.to_lowercase();
+            match i {
+                0 => part,
+                _ => {
+                    let mut chars = part.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                }
+            }
+        })
+        .collect()
+}
+
+fn json_to_toml(field: &str, value: serde_json::Value) -> Option<Value> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(bool) => Some(Value::from(bool)),
+        serde_json::Value::Number(number) => number
+            .as_i64()
+            .map(Value::from)
+            .or_else(|| number.as_f64().map(Value::from)),
+        serde_json::Value::String(string) => Some(parse_value(field, &string)),
+        serde_json::Value::Array(array) => Some(Value::Array(
+            array
+                .into_iter()
+                .filter_map(|value| json_to_toml(field, value))
+                .collect::<Array>(),
+        )),
+        serde_json::Value::Object(object) => Some(Value::InlineTable(
+            object
+                .into_iter()
+                .filter_map(|(key, value)| json_to_toml(&key, value).map(|value| (key, value)))
+                .collect::<InlineTable>(),
+        )),
+    }
+}
+
+/// Read the fields of a toml or json file, depending on its extension.
+fn read_file(path: &Path) -> Result<Vec<(String, Value)>, MainError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|error| MainError::ReadFile(error, Cow::Owned(path.to_path_buf())))?;
+
+    if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+    {
+        match serde_json::from_str(&contents) {
+            Ok(serde_json::Value::Object(object)) => Ok(object
+                .into_iter()
+                .filter_map(|(key, value)| json_to_toml(&key, value).map(|value| (key, value)))
+                .collect()),
+            Ok(_) => Err(MainError::ParseReleaseOverrides(
+                "expected a json object".to_string(),
+                Cow::Owned(path.to_path_buf()),
+            )),
+            Err(error) => Err(MainError::ParseReleaseOverrides(
+                error.to_string(),
+                Cow::Owned(path.to_path_buf()),
+            )),
+        }
+    } else {
+        contents
+            .parse::<DocumentMut>()
+            .map_err(|error| {
+                MainError::ParseReleaseOverrides(error.to_string(), Cow::Owned(path.to_path_buf()))
+            })
+            .map(|document| {
+                document
+                    .iter()
+                    .filter_map(|(key, item)| {
+                        item.as_value()
+                            .map(|value| (key.to_string(), value.clone()))
+                    })
+                    .collect()
+            })
+    }
+}
+
+/// Overrides collected from `--from-file`, `NEIGHBORHOOD_RELEASE_*` and `--set`, in increasing precedence.
+pub struct Overrides(Vec<(String, Value)>);
+impl Overrides {
+    pub fn new<I>(from_file: Option<&Path>, set: I) -> Result<Self, MainError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut overrides = from_file.map(read_file).transpose()?.unwrap_or_default();
+
+        overrides.extend(env::vars_os().filter_map(|(key, value)| {
+            let field = key.to_str()?.strip_prefix(ENV_PREFIX).map(env_to_field)?;
+            let value = parse_value(&field, value.to_str()?);
+            Some((field, value))
+        }));
+
+        for set in set {
+            let (field, value) = set
+                .split_once('=')
+                .ok_or_else(|| MainError::InvalidReleaseOverride(set.clone()))?;
+            overrides.push((field.to_string(), parse_value(field, value)));
+        }
+
+        overrides
+            .iter()
+            .try_for_each(|(field, _)| check_field(field))
+            .map(|_| Self(overrides))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Apply the overrides, keeping the comments of existing fields.
+    pub fn apply(&self, document: &mut DocumentMut) {
+        self.0
+            .iter()
+            .for_each(|(field, value)| set_value(document, field, value.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields() {
+        assert_eq!(env_to_field("PLAYABLE_URL"), "playableUrl");
+        assert_eq!(env_to_field("ADDRESS_LINE_1"), "addressLine1");
+        assert_eq!(env_to_field("NEW_SCREENSHOT_PATHS"), "newScreenshotPaths");
+        FIELDS
+            .iter()
+            .chain(profile::FIELDS)
+            .for_each(|field| check_field(field).unwrap());
+        assert!(check_field("playableURL").is_err());
+        [
+            super::super::INITIAL_RELEASE_CONFIG,
+            profile::INITIAL_PROFILE,
+        ]
+        .into_iter()
+        .flat_map(|template| template.parse::<DocumentMut>().unwrap().into_table())
+        .filter(|(field, _)| field.as_str() != "version")
+        .for_each(|(field, _)| check_field(&field).unwrap());
+
+        assert_eq!(
+            parse_value("description", "[not an array]").as_str(),
+            Some("[not an array]")
+        );
+        assert!(parse_value("screenshots", r#"["a", "b"]"#).is_array());
+        assert!(parse_value("birthday", "2000-01-01").is_datetime());
+    }
+
+    #[test]
+    fn apply() {
+        let mut document = super::super::INITIAL_RELEASE_CONFIG
+            .parse::<DocumentMut>()
+            .unwrap();
+        Overrides(vec![
+            (
+                "playableUrl".to_string(),
+                Value::from("https://example.com"),
+            ),
+            ("email".to_string(), Value::from("me@example.com")),
+        ])
+        .apply(&mut document);
+
+        let document = document.to_string();
+        assert!(document.contains(r#"playableUrl = "https://example.com" # Link that showcases"#));
+        assert!(document.contains(r#"email = "me@example.com""#));
+    }
+}
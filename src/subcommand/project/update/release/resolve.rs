@@ -0,0 +1,268 @@
+//! Includes and `{{ name }}` variables in release forms, so that answers can be shared between projects
+
+use {
+    crate::{MainError, cache::write_file, form::set_value},
+    serde::de::Error as _,
+    std::{
+        borrow::Cow,
+        fs, io,
+        path::{Path, PathBuf},
+    },
+    toml_edit::{DocumentMut, Formatted, Item, TomlError, Value},
+};
+
+const INCLUDE_KEY: &str = "include";
+/// The key of paths that are relative to the root of the project like includes.
+const PATHS_KEY: &str = "newScreenshotPaths";
+
+fn error(message: String) -> TomlError {
+    TomlError::from(toml_edit::de::Error::custom(message))
+}
+
+/// Remove the `include` key of `document`, returning the paths it lists.
+fn includes(document: &mut DocumentMut) -> Result<Vec<PathBuf>, TomlError> {
+    match document.remove(INCLUDE_KEY) {
+        None => Ok(Vec::new()),
+        Some(item) => match item.as_str() {
+            Some(path) => Ok(vec![PathBuf::from(path)]),
+            None => item
+                .as_array()
+                .and_then(|paths| {
+                    paths
+                        .iter()
+                        .map(|path| path.as_str().map(PathBuf::from))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| {
+                    error(format!(
+                        "`{INCLUDE_KEY}` should be a path or an array of paths"
+                    ))
+                }),
+        },
+    }
+}
+
+/// Add the fields of every file included by `document` that it leaves unset or empty, like the
+/// ones of a new form.
+///
+/// Paths are relative to `dir`, and earlier includes take precedence over later ones. `stack`
+/// holds the files currently being included, to catch files that include themselves.
+fn include(
+    document: &mut DocumentMut,
+    dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<(), TomlError> {
+    for path in includes(document)? {
+        let path = dir.join(path);
+        let path = path
+            .canonicalize()
+            .map_err(|e| error(format!("failed to include `{}`: {e}", path.display())))?;
+        if stack.contains(&path) {
+            return Err(error(format!("`{}` includes itself", path.display())));
+        }
+
+        let mut included = fs::read_to_string(&path)
+            .map_err(|e| error(format!("failed to include `{}`: {e}", path.display())))?
+            .parse::<DocumentMut>()
+            .map_err(|e| error(format!("failed to include `{}`: {e}", path.display())))?;
+        let parent = path.parent().unwrap_or(dir).to_path_buf();
+        stack.push(path);
+        include(&mut included, &parent, stack)?;
+        stack.pop();
+
+        included.into_table().into_iter().for_each(|(key, item)| {
+            match (document.get(&key), item) {
+                (None, item) => {
+                    document.insert(&key, item);
+                }
+                (Some(old), Item::Value(value)) if old.as_str() == Some("") => {
+                    set_value(document, &key, value)
+                }
+                _ => {}
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Replace every `{{ name }}` in `string` with the value of the variable.
+fn interpolate<F>(string: &str, var: &F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(string.len());
+    let mut rest = string;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let (name, after) = rest[start + 2..]
+            .split_once("}}")
+            .ok_or_else(|| "unterminated `{{`".to_string())?;
+        let name = name.trim();
+        output.push_str(&var(name).ok_or_else(|| format!("unknown variable `{name}`"))?);
+        rest = after;
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+fn interpolate_value<F>(value: &mut Value, var: &F) -> Result<(), String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    match value {
+        // strings without variables are left alone to keep their formatting
+        Value::String(string) if string.value().contains("{{") => {
+            let decor = string.decor().clone();
+            *string = Formatted::new(interpolate(string.value(), var)?);
+            *string.decor_mut() = decor;
+            Ok(())
+        }
+        Value::Array(array) => array
+            .iter_mut()
+            .try_for_each(|value| interpolate_value(value, var)),
+        Value::InlineTable(table) => table
+            .iter_mut()
+            .try_for_each(|(_, value)| interpolate_value(value, var)),
+        _ => Ok(()),
+    }
+}
+
+/// Merge the included files into a copy of the form and fill in its variables.
+///
+/// Includes and the paths of `newScreenshotPaths` in the form are relative to `dir`, the root of
+/// its project.
+pub fn resolve<F>(document: &DocumentMut, dir: &Path, var: F) -> Result<DocumentMut, TomlError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut document = document.clone();
+    include(&mut document, dir, &mut Vec::new())?;
+
+    document
+        .iter_mut()
+        .filter_map(|(key, item)| item.as_value_mut().map(|value| (key, value)))
+        .try_for_each(|(key, value)| {
+            interpolate_value(value, &var).map_err(|e| error(format!("{key}: {e}")))
+        })?;
+
+    if let Some(paths) = document.get_mut(PATHS_KEY).and_then(Item::as_array_mut) {
+        paths
+            .iter_mut()
+            .filter_map(|path| Some((dir.join(path.as_str()?), path)))
+            .for_each(|(joined, path)| *path = Value::from(joined.to_string_lossy().as_ref()));
+    }
+
+    Ok(document)
+}
+
+fn root_path(form: &Path) -> PathBuf {
+    form.with_extension("root")
+}
+
+/// The root of the project of the form at `form`, which its includes are relative to.
+///
+/// This is the root recorded with [record_root] so that the form resolves the same way when it is
+/// used from another directory, or `dir` for new forms and ones being `reset`.
+pub fn root(form: &Path, dir: &Path, reset: bool) -> Result<PathBuf, MainError> {
+    let path = root_path(form);
+    match fs::read_to_string(&path) {
+        Ok(root) if !reset => Ok(PathBuf::from(root.trim_end())),
+        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+            Err(MainError::ReadFile(error, Cow::Owned(path)))
+        }
+        _ => Ok(dir.to_path_buf()),
+    }
+}
+
+/// Record `root` next to the form at `form`, which should only be done once the form is saved.
+pub fn record_root(form: &Path, root: &Path) -> Result<(), MainError> {
+    write_file(
+        Cow::Owned(root_path(form)),
+        root.to_string_lossy().as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::tempdir};
+
+    #[test]
+    fn includes_and_vars() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("team")).unwrap();
+        fs::write(
+            dir.path().join("team/shared.toml"),
+            "include = \"answers.toml\"\ndescription = \"shared\"\nhowDidYouHear = \"{{ source }}\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("team/answers.toml"),
+            "howDidYouHear = \"nested\"\nhowCanWeImprove = \"{{ team }}\"\n",
+        )
+        .unwrap();
+        let var = |name: &str| match name {
+            "team" => Some("the team".to_string()),
+            "source" => Some("a friend".to_string()),
+            _ => None,
+        };
+
+        let document = "include = [\"team/shared.toml\"]\ndescription = \"{{team}} app\" # kept\nhowCanWeImprove = \"\" # filled\n"
+            .parse::<DocumentMut>()
+            .unwrap();
+        let resolved = resolve(&document, dir.path(), var).unwrap();
+        assert!(!resolved.contains_key(INCLUDE_KEY));
+        assert_eq!(
+            resolved.to_string().lines().next(),
+            Some(r#"description = "the team app" # kept"#)
+        );
+        assert_eq!(resolved["howDidYouHear"].as_str(), Some("a friend"));
+        assert!(
+            resolved
+                .to_string()
+                .contains(r#"howCanWeImprove = "the team" # filled"#)
+        );
+
+        let document = "description = \"{{ missing }}\"".parse().unwrap();
+        let error = resolve(&document, dir.path(), var).unwrap_err();
+        assert!(error.to_string().contains("unknown variable `missing`"));
+
+        fs::write(
+            dir.path().join("team/answers.toml"),
+            "include = \"shared.toml\"",
+        )
+        .unwrap();
+        let document = "include = \"team/shared.toml\"".parse().unwrap();
+        let error = resolve(&document, dir.path(), var).unwrap_err();
+        assert!(error.to_string().contains("includes itself"));
+    }
+
+    #[test]
+    fn recorded_root() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("shared.toml"), "description = \"shared\"").unwrap();
+        let form = dir.path().join("project.toml");
+
+        assert_eq!(root(&form, &project, true).unwrap(), project);
+        // nothing is recorded until the form is saved
+        assert_eq!(root(&form, dir.path(), false).unwrap(), dir.path());
+        record_root(&form, &project).unwrap();
+        // later runs from a subdirectory keep using the recorded root
+        let root = root(&form, &project.join("src"), false).unwrap();
+        assert_eq!(root, project);
+
+        let document = "include = \"shared.toml\"\nnewScreenshotPaths = [\"a.png\", \"/b.png\"]"
+            .parse()
+            .unwrap();
+        let resolved = resolve(&document, &root, |_| None).unwrap();
+        assert_eq!(resolved["description"].as_str(), Some("shared"));
+        assert_eq!(
+            resolved[PATHS_KEY][0].as_str(),
+            project.join("a.png").to_str()
+        );
+        assert_eq!(resolved[PATHS_KEY][1].as_str(), Some("/b.png"));
+    }
+}
@@ -0,0 +1,49 @@
+//! Updates waiting for the network to come back
+
+use {
+    crate::{MainError, api, config::Config, history, outbox},
+    clap::ArgMatches,
+};
+
+pub fn execute(mut args: ArgMatches, config: &Config) -> Result<(), MainError> {
+    let (subcommand, mut args) = args.remove_subcommand().unwrap();
+    match subcommand.as_str() {
+        "list" => outbox::read(config)?
+            .iter()
+            .enumerate()
+            .for_each(|(i, (_, pending))| {
+                println!(
+                    "{}: {} {} {} {}",
+                    i + 1,
+                    pending.time.format("%Y-%m-%d %H:%M:%S UTC"),
+                    pending.project,
+                    pending.operation.kind(),
+                    pending.operation.summary()
+                )
+            }),
+        "flush" => match outbox::flush(config, super::project::update::replay).map_err(|error| {
+            match api::is_offline(&error) {
+                true => error,
+                false => MainError::QueueStuck(Box::new(error)),
+            }
+        })? {
+            0 => eprintln!("There are no queued updates"),
+            sent => eprintln!("Sent {sent} queued updates"),
+        },
+        "drop" => {
+            let n = args.remove_one::<usize>("n").unwrap();
+            let queued = outbox::read(config)?;
+            let (path, pending) = history::nth(&queued, n).ok_or(MainError::UnknownQueued(n))?;
+            outbox::remove(path)?;
+            eprintln!(
+                "Dropped the {} of `{}` from {}",
+                pending.operation.kind(),
+                pending.project,
+                pending.time.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use {
+    crate::{MainError, schema},
+    clap::ArgMatches,
+    std::io::{self, Write},
+};
+
+pub fn execute(args: ArgMatches) -> Result<(), MainError> {
+    let schema = match args.get_one::<String>("form").unwrap().as_str() {
+        "profile" => schema::profile(),
+        "release" => super::project::update::release::schema(),
+        _ => unreachable!(),
+    };
+
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &schema)
+        .map_err(io::Error::from)
+        .and_then(|_| writeln!(stdout))
+        .and_then(|_| stdout.flush())
+        .map_err(MainError::WriteStdout)
+}
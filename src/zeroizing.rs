@@ -1 +0,0 @@
-use zeroize::Zeroize;